@@ -1,19 +1,50 @@
+mod backups;
+mod error;
+mod install_detection;
+mod presets;
+mod transaction;
+
 use anyhow::Result;
 use futures::future::join_all;
 use log::{error, info};
 use serde_json::json;
-use std::{collections::BTreeMap, path::PathBuf};
-use tauri::{path::BaseDirectory, AppHandle, Manager, State, Wry};
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tauri::{path::BaseDirectory, AppHandle, Emitter, Manager, State, Wry};
 use tauri_plugin_store::{with_store, Store, StoreCollection};
 use tokio::{
     fs::{read_to_string, File},
     io::{self, AsyncReadExt, AsyncWriteExt},
+    sync::Mutex,
 };
 
+use backups::{Backup, BackupStore, KEEP_COUNT};
+use error::AppError;
+use install_detection::Candidate;
+use presets::{Preset, Presets};
+use transaction::Transaction;
+
 use crate::{
-    dataset::game_structure::GameStructureFiles,
-    randomizer::{randomize, RandomizeOptions, SpoilerLog},
-    script::file::scriptconverter::is_valid_script_dat,
+    dataset::game_structure::{
+        field_logic_number, GameStructure, GameStructureFiles, EVENTS_FILE_PATH, FIELD_FILE_PATHS,
+    },
+    randomizer::{
+        progress::{Cancelled, Progress, Stage},
+        randomize, seed_banner, spoiler_encryption,
+        spoiler_view::SpoilerView,
+        verify_script_dat, RandomizeOptions,
+    },
+    script::file::{
+        patch,
+        scriptconverter::{check_script_dat, is_valid_script_dat},
+    },
 };
 
 #[derive(serde::Serialize)]
@@ -82,11 +113,14 @@ impl InitialData {
 }
 
 #[tauri::command]
-pub fn initial_data(app_handle: AppHandle, stores: State<StoreCollection<Wry>>) -> InitialData {
-    with_store(app_handle, stores, PathBuf::from("store.json"), |store| {
+pub fn initial_data(
+    app_handle: AppHandle,
+    stores: State<StoreCollection<Wry>>,
+) -> Result<InitialData, AppError> {
+    let data = with_store(app_handle, stores, PathBuf::from("store.json"), |store| {
         Ok(InitialData::read(store))
-    })
-    .unwrap()
+    })?;
+    Ok(data)
 }
 
 #[tauri::command]
@@ -102,7 +136,8 @@ fn set_initial_data_value<T>(
     app_handle: AppHandle,
     stores: State<StoreCollection<Wry>>,
     callback: impl FnOnce(&mut InitialData) -> T,
-) where
+) -> Result<(), AppError>
+where
     T: serde::Serialize,
 {
     with_store(app_handle, stores, PathBuf::from("store.json"), |store| {
@@ -111,13 +146,128 @@ fn set_initial_data_value<T>(
         data.write(store)?;
         store.save()?;
         Ok(())
+    })?;
+    Ok(())
+}
+
+fn preset_failed(err: anyhow::Error) -> AppError {
+    AppError::PresetFailed {
+        details: format!("{:#}", err),
+    }
+}
+
+/// The presets of store.json. Fails if they can't be read rather than lose them on the next save.
+fn load_presets(store: &Store<Wry>) -> Result<Presets> {
+    let Some(obj) = store.get("presets") else {
+        return Ok(Presets::default());
+    };
+    Ok(serde_json::from_value(obj.clone())?)
+}
+
+/// Runs `callback` on the presets of store.json without saving them.
+fn read_presets<T>(
+    app_handle: AppHandle,
+    stores: State<StoreCollection<Wry>>,
+    callback: impl FnOnce(&Presets) -> Result<T>,
+) -> Result<T, AppError> {
+    with_store(app_handle, stores, PathBuf::from("store.json"), |store| {
+        Ok(load_presets(store).and_then(|presets| callback(&presets)))
+    })?
+    .map_err(preset_failed)
+}
+
+/// Runs `callback` on the presets of store.json and saves them if it succeeds.
+fn with_presets<T>(
+    app_handle: AppHandle,
+    stores: State<StoreCollection<Wry>>,
+    callback: impl FnOnce(&mut Presets) -> Result<T>,
+) -> Result<T, AppError> {
+    with_store(app_handle, stores, PathBuf::from("store.json"), |store| {
+        let mut presets = match load_presets(store) {
+            Ok(presets) => presets,
+            Err(e) => return Ok(Err(e)),
+        };
+        let result = callback(&mut presets);
+        if result.is_ok() {
+            store.insert("presets".to_owned(), json!(presets))?;
+            store.save()?;
+        }
+        Ok(result)
+    })?
+    .map_err(preset_failed)
+}
+
+#[tauri::command]
+pub fn list_presets(
+    app_handle: AppHandle,
+    stores: State<StoreCollection<Wry>>,
+) -> Result<Vec<Preset>, AppError> {
+    read_presets(app_handle, stores, |presets| Ok(presets.all()))
+}
+
+#[tauri::command]
+pub fn save_preset(
+    app_handle: AppHandle,
+    stores: State<StoreCollection<Wry>>,
+    name: String,
+    options: RandomizeOptions,
+) -> Result<(), AppError> {
+    with_presets(app_handle, stores, |presets| presets.save(name, options))
+}
+
+#[tauri::command]
+pub fn rename_preset(
+    app_handle: AppHandle,
+    stores: State<StoreCollection<Wry>>,
+    name: String,
+    new_name: String,
+) -> Result<(), AppError> {
+    with_presets(app_handle, stores, |presets| {
+        presets.rename(&name, new_name)
     })
-    .unwrap();
 }
 
 #[tauri::command]
-pub fn set_seed(app_handle: AppHandle, stores: State<StoreCollection<Wry>>, value: String) {
-    set_initial_data_value(app_handle, stores, |data| data.seed = value);
+pub fn delete_preset(
+    app_handle: AppHandle,
+    stores: State<StoreCollection<Wry>>,
+    name: String,
+) -> Result<(), AppError> {
+    with_presets(app_handle, stores, |presets| presets.delete(&name))
+}
+
+#[tauri::command]
+pub async fn export_preset(
+    app_handle: AppHandle,
+    stores: State<'_, StoreCollection<Wry>>,
+    name: String,
+    file_path: String,
+) -> Result<(), AppError> {
+    let json = read_presets(app_handle, stores, |presets| presets.export(&name))?;
+    write_file(&file_path, json.as_bytes())
+        .await
+        .map_err(|e| AppError::write_failed(&file_path, e))
+}
+
+#[tauri::command]
+pub async fn import_preset(
+    app_handle: AppHandle,
+    stores: State<'_, StoreCollection<Wry>>,
+    file_path: String,
+) -> Result<Preset, AppError> {
+    let json = read_to_string(&file_path)
+        .await
+        .map_err(|e| preset_failed(e.into()))?;
+    with_presets(app_handle, stores, |presets| presets.import(&json))
+}
+
+#[tauri::command]
+pub fn set_seed(
+    app_handle: AppHandle,
+    stores: State<StoreCollection<Wry>>,
+    value: String,
+) -> Result<(), AppError> {
+    set_initial_data_value(app_handle, stores, |data| data.seed = value)
 }
 
 #[tauri::command]
@@ -125,13 +275,17 @@ pub fn set_install_directory(
     app_handle: AppHandle,
     stores: State<StoreCollection<Wry>>,
     value: String,
-) {
-    set_initial_data_value(app_handle, stores, |data| data.install_directory = value);
+) -> Result<(), AppError> {
+    set_initial_data_value(app_handle, stores, |data| data.install_directory = value)
 }
 
 #[tauri::command]
-pub fn set_easy_mode(app_handle: AppHandle, stores: State<StoreCollection<Wry>>, value: bool) {
-    set_initial_data_value(app_handle, stores, |data| data.easy_mode = value);
+pub fn set_easy_mode(
+    app_handle: AppHandle,
+    stores: State<StoreCollection<Wry>>,
+    value: bool,
+) -> Result<(), AppError> {
+    set_initial_data_value(app_handle, stores, |data| data.easy_mode = value)
 }
 
 #[tauri::command]
@@ -139,13 +293,17 @@ pub fn set_shuffle_secret_roms(
     app_handle: AppHandle,
     stores: State<StoreCollection<Wry>>,
     value: bool,
-) {
-    set_initial_data_value(app_handle, stores, |data| data.shuffle_secret_roms = value);
+) -> Result<(), AppError> {
+    set_initial_data_value(app_handle, stores, |data| data.shuffle_secret_roms = value)
 }
 
 #[tauri::command]
-pub fn set_need_glitches(app_handle: AppHandle, stores: State<StoreCollection<Wry>>, value: bool) {
-    set_initial_data_value(app_handle, stores, |data| data.need_glitches = value);
+pub fn set_need_glitches(
+    app_handle: AppHandle,
+    stores: State<StoreCollection<Wry>>,
+    value: bool,
+) -> Result<(), AppError> {
+    set_initial_data_value(app_handle, stores, |data| data.need_glitches = value)
 }
 
 #[tauri::command]
@@ -153,8 +311,8 @@ pub fn set_absolutely_shuffle(
     app_handle: AppHandle,
     stores: State<StoreCollection<Wry>>,
     value: bool,
-) {
-    set_initial_data_value(app_handle, stores, |data| data.absolutely_shuffle = value);
+) -> Result<(), AppError> {
+    set_initial_data_value(app_handle, stores, |data| data.absolutely_shuffle = value)
 }
 
 async fn read_file(path: &str) -> io::Result<Vec<u8>> {
@@ -168,6 +326,15 @@ async fn read_file(path: &str) -> io::Result<Vec<u8>> {
     Ok(contents)
 }
 
+/// Failures are only logged, as the new files are already in place.
+async fn remove_stale_file(path: &str) {
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => info!("Removed file: {}", path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => error!("Failed to remove {}: {:?}", path, e),
+    }
+}
+
 async fn write_file(path: &str, contents: &[u8]) -> io::Result<()> {
     info!("Writing file: {}", path);
     let mut file = File::create(path).await?;
@@ -178,27 +345,7 @@ async fn write_file(path: &str, contents: &[u8]) -> io::Result<()> {
 async fn read_game_structure_files_internal(
     resolve_path: impl Fn(&str) -> Result<PathBuf>,
 ) -> anyhow::Result<GameStructureFiles> {
-    let file_paths = [
-        "res/00_Surface.yml",
-        "res/01_Gate_of_Guidance.yml",
-        "res/02_Mausoleum_of_the_Giants.yml",
-        "res/03_Temple_of_the_Sun.yml",
-        "res/04_Spring_in_the_Sky.yml",
-        "res/05_Inferno_Cavern.yml",
-        "res/06_Chamber_of_Extinction.yml",
-        "res/07_Twin_Labyrinths_Left.yml",
-        "res/08_Endless_Corridor.yml",
-        "res/09_Shrine_of_the_Mother.yml",
-        "res/11_Gate_of_Illusion.yml",
-        "res/12_Graveyard_of_the_Giants.yml",
-        "res/13_Temple_of_Moonlight.yml",
-        "res/14_Tower_of_the_Goddess.yml",
-        "res/15_Tower_of_Ruin.yml",
-        "res/16_Chamber_of_Birth.yml",
-        "res/17_Twin_Labyrinths_Right.yml",
-        "res/18_Dimensional_Corridor.yml",
-        "res/19_True_Shrine_of_the_Mother.yml",
-    ];
+    let file_paths = FIELD_FILE_PATHS;
     #[allow(clippy::redundant_closure)]
     let futures: Vec<_> = file_paths
         .map(|file_path| resolve_path(file_path))
@@ -213,9 +360,9 @@ async fn read_game_structure_files_internal(
         .collect::<io::Result<Vec<_>>>()?
         .into_iter()
         .zip(file_paths)
-        .map(|(contents, file_path)| (file_path[4..6].parse::<u8>().unwrap(), contents))
+        .map(|(contents, file_path)| (field_logic_number(file_path), contents))
         .collect();
-    let events = read_to_string(resolve_path("res/events.yml")?).await?;
+    let events = read_to_string(resolve_path(EVENTS_FILE_PATH)?).await?;
 
     GameStructureFiles::new(fields, events)
 }
@@ -233,70 +380,498 @@ pub async fn read_game_structure_files_debug() -> Result<GameStructureFiles> {
     read_game_structure_files_internal(|file_path| Ok(PathBuf::from(file_path))).await
 }
 
+/// The vanilla script.dat can't be in the repository, so the tests that need it read it from
+/// `LMO_SCRIPT_DAT` and are ignored by default.
+#[allow(unused)]
+pub fn read_script_dat_debug() -> Result<Vec<u8>> {
+    let path = std::env::var("LMO_SCRIPT_DAT")?;
+    Ok(std::fs::read(path)?)
+}
+
+/// Held by the commands that write into the install so that they can't interleave.
+static INSTALL_LOCK: Mutex<()> = Mutex::const_new(());
+
+fn transaction_failed((path, err): (PathBuf, io::Error)) -> AppError {
+    AppError::write_failed(&path.to_string_lossy(), err)
+}
+
+fn game_structure_read_failed(err: anyhow::Error) -> AppError {
+    AppError::GameStructureReadFailed {
+        details: err.to_string(),
+    }
+}
+
+/// The spoiler of the last `apply` that succeeded, for `spoiler`.
+/// Cleared when another script.dat is put into the install, since it no longer matches.
+static LAST_SPOILER: std::sync::Mutex<Option<SpoilerView>> = std::sync::Mutex::new(None);
+
+/// Set by `cancel` and cleared when `apply` starts.
+static CANCEL_REQUESTED: AtomicBool = AtomicBool::new(false);
+/// Shuffle attempts are reported at most this often.
+const SHUFFLE_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Emits the stages of `apply` as `progress` events.
+struct ProgressEmitter {
+    handle: AppHandle,
+    last_shuffle: std::sync::Mutex<Option<Instant>>,
+}
+
+impl ProgressEmitter {
+    fn new(handle: AppHandle) -> Self {
+        Self {
+            handle,
+            last_shuffle: Default::default(),
+        }
+    }
+}
+
+impl Progress for ProgressEmitter {
+    fn report(&self, stage: Stage) {
+        if let Stage::Shuffle { .. } = stage {
+            let mut last_shuffle = self.last_shuffle.lock().unwrap();
+            if last_shuffle.is_some_and(|x| x.elapsed() < SHUFFLE_PROGRESS_INTERVAL) {
+                return;
+            }
+            *last_shuffle = Some(Instant::now());
+        }
+        if let Err(e) = self.handle.emit("progress", stage) {
+            error!("{:?}", e);
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        CANCEL_REQUESTED.load(Ordering::Relaxed)
+    }
+}
+
+/// Stops the shuffle of the running `apply`, which then fails with `cancelled`.
+#[tauri::command]
+pub fn cancel() {
+    CANCEL_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+const SPOILER_LOG_FILE_NAME: &str = "spoilerlog.txt";
+const ENCRYPTED_SPOILER_LOG_FILE_NAME: &str = "spoilerlog.enc";
+
+/// A spoiler log of an earlier seed must not be left next to the one just `written`.
+async fn remove_stale_spoiler_logs(directory: &str, written: Option<&str>) {
+    for name in [SPOILER_LOG_FILE_NAME, ENCRYPTED_SPOILER_LOG_FILE_NAME] {
+        if written != Some(name) {
+            remove_stale_file(&format!("{}/{}", directory, name)).await;
+        }
+    }
+}
+
+/// What `apply` does with the spoiler log. Races need it out of reach of the racers.
+#[derive(Default, serde::Deserialize)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum SpoilerPolicy {
+    /// Neither written nor returned.
+    None,
+    #[default]
+    Plaintext,
+    /// Written as spoilerlog.enc, which only `decrypt_spoiler_log` with the password can read.
+    Encrypted { password: String },
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApplyOptions {
+    /// Writes nothing, not even the backup, and only returns the spoiler log.
+    dry_run: bool,
+    /// Writes script.dat and the spoiler log here instead of `<install>/data`.
+    output_directory: Option<String>,
+    spoiler_policy: SpoilerPolicy,
+    /// Accepts a script.dat of an unknown version if it passes the structural validation.
+    allow_unknown_version: bool,
+    /// Backs up a script.dat of an unknown version as the vanilla one. Confirmed apart from
+    /// `allow_unknown_version` because every later seed and `restore` build on it.
+    confirm_unknown_vanilla: bool,
+    /// Reads the placements back from the output and checks them against the logic.
+    verify: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyResult {
+    /// `None` unless the spoiler policy is plaintext.
+    spoiler_log: Option<String>,
+    /// Where script.dat was written, unless it was a dry run.
+    script_dat_path: Option<String>,
+}
+
 #[tauri::command]
 pub async fn apply(
     handle: AppHandle,
     install_directory: String,
     options: RandomizeOptions,
-) -> String {
+    apply_options: Option<ApplyOptions>,
+) -> Result<ApplyResult, AppError> {
     log::trace!("{}", install_directory);
-    let target_file_path = format!("{}/data/script.dat", install_directory);
-    let backup_file_path = format!("{}/data/script.dat.bak", install_directory);
+    let apply_options = apply_options.unwrap_or_default();
+    let _lock = INSTALL_LOCK.lock().await;
+    CANCEL_REQUESTED.store(false, Ordering::Relaxed);
+    let progress = Arc::new(ProgressEmitter::new(handle.clone()));
 
-    let working = if let Some(working) = read_valid_file_or_null(&backup_file_path).await {
-        working
-    } else {
-        let Some(working) = read_file(&target_file_path).await.ok() else {
-            return "Unable to find La-Mulana install directory.".to_owned();
-        };
-        if !is_valid_script_dat(&working) {
-            return "Valid script is not found. Please re-install La-Mulana.".to_owned();
+    // The install is left untouched unless the output goes there.
+    let create_backup = !apply_options.dry_run && apply_options.output_directory.is_none();
+    let working = read_working(
+        &install_directory,
+        create_backup,
+        apply_options.allow_unknown_version,
+        apply_options.confirm_unknown_vanilla,
+    )
+    .await?;
+    let game_structure = read_game_structure_files(handle)
+        .await
+        .and_then(GameStructure::new)
+        .map_err(game_structure_read_failed)?;
+
+    let generation_progress = progress.clone();
+    let blocking = tauri::async_runtime::spawn_blocking(move || {
+        let generated = randomize(&working, &game_structure, &options, &*generation_progress);
+        (working, game_structure, options, generated)
+    });
+    let (working, game_structure, options, generated) =
+        blocking.await.map_err(|e| AppError::GenerationFailed {
+            details: e.to_string(),
+        })?;
+    let (randomized, spoiler_log) = generated.map_err(|e| {
+        if e.is::<Cancelled>() {
+            return AppError::Cancelled;
         }
-        if let Err(err) = write_valid_script_dat(&backup_file_path, &working).await {
-            return format!("Failed to backup script.dat: {}", err);
+        error!("{:?}", e);
+        AppError::GenerationFailed {
+            details: format!("{:#}", e),
         }
-        working
-    };
-    let game_structure = match read_game_structure_files(handle).await {
-        Ok(ok) => ok,
-        Err(err) => return format!("Failed to read game structure files: {}", err),
-    };
+    })?;
 
-    let (randomized, spoiler_log) = match randomize(&working, game_structure, &options) {
-        Ok(randomized) => randomized,
-        Err(e) => {
+    if apply_options.verify {
+        let spoiler_log = spoiler_log.to_string();
+        verify_script_dat(
+            &working,
+            &randomized,
+            &game_structure,
+            &options,
+            &spoiler_log,
+        )
+        .map_err(|e| {
             error!("{:?}", e);
-            return format!("Randomization failed: {}", e);
+            AppError::VerificationFailed {
+                details: format!("{:#}", e),
+            }
+        })?;
+    }
+
+    let plaintext = matches!(apply_options.spoiler_policy, SpoilerPolicy::Plaintext);
+    let spoiler_view = plaintext.then(|| SpoilerView::new(&spoiler_log));
+    let banner = seed_banner(&options, &spoiler_log);
+    let spoiler_log = format!("{}\n\n{}", banner, spoiler_log);
+    let spoiler_log_file = match &apply_options.spoiler_policy {
+        SpoilerPolicy::None => None,
+        SpoilerPolicy::Plaintext => Some((SPOILER_LOG_FILE_NAME, spoiler_log.as_bytes().to_vec())),
+        SpoilerPolicy::Encrypted { password } => {
+            let encrypted = spoiler_encryption::encrypt(&spoiler_log, password).map_err(|e| {
+                AppError::SpoilerEncryptionFailed {
+                    details: format!("{:#}", e),
+                }
+            })?;
+            Some((ENCRYPTED_SPOILER_LOG_FILE_NAME, encrypted))
         }
     };
+    let spoiler_log = plaintext.then_some(spoiler_log);
+    if apply_options.dry_run {
+        *LAST_SPOILER.lock().unwrap() = spoiler_view;
+        return Ok(ApplyResult {
+            spoiler_log,
+            script_dat_path: None,
+        });
+    }
 
-    if let Err(err) = write_file(&target_file_path, &randomized).await {
-        return format!("Failed to write randomized script.dat: {}", err);
+    let in_install = apply_options.output_directory.is_none();
+    let output_directory = match apply_options.output_directory {
+        Some(output_directory) => {
+            tokio::fs::create_dir_all(&output_directory)
+                .await
+                .map_err(|e| AppError::write_failed(&output_directory, e))?;
+            output_directory
+        }
+        None => format!("{}/data", install_directory),
+    };
+    progress.report(Stage::Write);
+    let script_dat_path = format!("{}/script.dat", output_directory);
+    let spoiler_log_file_path = spoiler_log_file
+        .as_ref()
+        .map(|(name, contents)| (format!("{}/{}", output_directory, name), contents));
+    // The spoiler log goes first so that script.dat is never replaced without it.
+    let files: Vec<(&str, &[u8])> = spoiler_log_file_path
+        .iter()
+        .map(|(path, contents)| (path.as_str(), contents.as_slice()))
+        .chain([(script_dat_path.as_str(), randomized.as_slice())])
+        .collect();
+    let mut transaction = Transaction::prepare(&files)
+        .await
+        .map_err(transaction_failed)?;
+    transaction.commit().await.map_err(transaction_failed)?;
+    if in_install {
+        let added = BackupStore::new(&install_directory)
+            .add(
+                &options.seed,
+                &options.settings_code(),
+                &randomized,
+                spoiler_log.as_deref(),
+                KEEP_COUNT,
+            )
+            .await;
+        if let Err(e) = added {
+            if let Err(e) = transaction.rollback().await {
+                error!("{:?}", e);
+            }
+            return Err(backup_failed(e));
+        }
     }
-    let spoiler_log_file_path = format!("{}/data/spoilerlog.txt", install_directory);
-    if let Err(err) = write_spoiler_log(&spoiler_log_file_path, &spoiler_log).await {
-        return format!("Failed to write spoiler log: {}", err);
+    *LAST_SPOILER.lock().unwrap() = spoiler_view;
+    let written = spoiler_log_file.map(|(name, _)| name);
+    remove_stale_spoiler_logs(&output_directory, written).await;
+    Ok(ApplyResult {
+        spoiler_log,
+        script_dat_path: Some(script_dat_path),
+    })
+}
+
+/// The spoiler of the last `apply` as data. Nothing is returned unless `reveal`, so that
+/// racers don't see it by accident.
+#[tauri::command]
+pub fn spoiler(reveal: bool) -> Result<Option<SpoilerView>, AppError> {
+    if !reveal {
+        return Ok(None);
+    }
+    match &*LAST_SPOILER.lock().unwrap() {
+        Some(spoiler) => Ok(Some(spoiler.clone())),
+        None => Err(AppError::SpoilerNotFound),
     }
-    "Succeeded.".to_owned()
 }
 
+/// Reads a spoiler log written with the encrypted policy, for the review after a race.
 #[tauri::command]
-pub async fn restore(install_directory: String) -> String {
+pub async fn decrypt_spoiler_log(file_path: String, password: String) -> Result<String, AppError> {
+    let decryption_failed = |details| AppError::SpoilerDecryptionFailed { details };
+    let encrypted = read_file(&file_path)
+        .await
+        .map_err(|e| decryption_failed(e.to_string()))?;
+    spoiler_encryption::decrypt(&encrypted, &password)
+        .map_err(|e| decryption_failed(format!("{:#}", e)))
+}
+
+#[tauri::command]
+pub async fn make_patch(
+    install_directory: String,
+    patch_file_path: String,
+) -> Result<(), AppError> {
     let target_file_path = format!("{}/data/script.dat", install_directory);
-    let backup_file_path = format!("{}/data/script.dat.bak", install_directory);
 
-    if let Ok(target_file) = read_file(&target_file_path).await {
-        if is_valid_script_dat(&target_file) {
-            return "Already clean.".to_owned();
+    let Some(working) = read_vanilla(&install_directory).await else {
+        return Err(AppError::BackupNotFound);
+    };
+    let Ok(randomized) = read_file(&target_file_path).await else {
+        return Err(AppError::InstallDirectoryNotFound);
+    };
+    if is_valid_script_dat(&randomized) || randomized == working {
+        return Err(AppError::NotRandomized);
+    }
+    let patch = patch::make_patch(&working, &randomized).map_err(|e| AppError::PatchFailed {
+        details: format!("{:#}", e),
+    })?;
+    write_file(&patch_file_path, &patch)
+        .await
+        .map_err(|e| AppError::write_failed(&patch_file_path, e))
+}
+
+#[tauri::command]
+pub async fn apply_patch(
+    install_directory: String,
+    patch_file_path: String,
+) -> Result<(), AppError> {
+    let target_file_path = format!("{}/data/script.dat", install_directory);
+
+    let _lock = INSTALL_LOCK.lock().await;
+    let working = read_working(&install_directory, true, false, false).await?;
+    let patch = read_file(&patch_file_path)
+        .await
+        .map_err(|e| AppError::PatchReadFailed {
+            details: e.to_string(),
+        })?;
+    let randomized = patch::apply_patch(&working, &patch).map_err(|e| AppError::PatchFailed {
+        details: format!("{:#}", e),
+    })?;
+    let mut transaction = Transaction::prepare(&[(&target_file_path, &randomized)])
+        .await
+        .map_err(transaction_failed)?;
+    transaction.commit().await.map_err(transaction_failed)?;
+    // A patch carries neither the seed nor the settings, nor a spoiler log.
+    let added = BackupStore::new(&install_directory)
+        .add("", "", &randomized, None, KEEP_COUNT)
+        .await;
+    if let Err(e) = added {
+        if let Err(e) = transaction.rollback().await {
+            error!("{:?}", e);
         }
+        return Err(backup_failed(e));
+    }
+    *LAST_SPOILER.lock().unwrap() = None;
+    remove_stale_spoiler_logs(&format!("{}/data", install_directory), None).await;
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RestoreOutcome {
+    Restored,
+    AlreadyClean,
+}
+
+#[tauri::command]
+pub async fn restore(install_directory: String) -> Result<RestoreOutcome, AppError> {
+    let target_file_path = format!("{}/data/script.dat", install_directory);
+
+    let _lock = INSTALL_LOCK.lock().await;
+    let target_file = read_file(&target_file_path).await.ok();
+    if target_file.as_deref().is_some_and(is_valid_script_dat) {
+        return Ok(RestoreOutcome::AlreadyClean);
     }
-    let Some(working) = read_valid_file_or_null(&backup_file_path).await else {
-        return "Backup is broken. Please re-install La-Mulana.".to_owned();
+    let Some(working) = read_vanilla(&install_directory).await else {
+        return Err(AppError::BackupBroken);
     };
-    if let Err(err) = write_valid_script_dat(&target_file_path, &working).await {
-        return format!("Failed to restore script.dat: {}", err);
+    if target_file.as_ref() == Some(&working) {
+        return Ok(RestoreOutcome::AlreadyClean);
+    }
+    Transaction::prepare(&[(&target_file_path, &working)])
+        .await
+        .map_err(transaction_failed)?
+        .commit()
+        .await
+        .map_err(transaction_failed)?;
+    *LAST_SPOILER.lock().unwrap() = None;
+    Ok(RestoreOutcome::Restored)
+}
+
+/// Installs found in `search_roots` and the usual locations, the best first.
+#[tauri::command]
+pub async fn detect_install_directories(search_roots: Vec<String>) -> Vec<Candidate> {
+    install_detection::detect_install_directories(&search_roots).await
+}
+
+#[tauri::command]
+pub async fn list_backups(install_directory: String) -> Result<Vec<Backup>, AppError> {
+    BackupStore::new(&install_directory)
+        .list()
+        .await
+        .map_err(backup_read_failed)
+}
+
+/// Puts a randomized script.dat of the backups and its spoiler log back into the install.
+#[tauri::command]
+pub async fn restore_backup(install_directory: String, id: String) -> Result<(), AppError> {
+    let _lock = INSTALL_LOCK.lock().await;
+    let store = BackupStore::new(&install_directory);
+    let backups = store.list().await.map_err(backup_read_failed)?;
+    if !backups.iter().any(|x| x.id == id) {
+        return Err(AppError::UnknownBackup { id });
     }
-    "Succeeded.".to_owned()
+    let (script_dat, spoiler_log) = store.read(&id).await.map_err(backup_read_failed)?;
+    let target_file_path = format!("{}/data/script.dat", install_directory);
+    let spoiler_log_file_path = format!("{}/data/{}", install_directory, SPOILER_LOG_FILE_NAME);
+    let mut files: Vec<(&str, &[u8])> = Vec::new();
+    if let Some(spoiler_log) = &spoiler_log {
+        files.push((&spoiler_log_file_path, spoiler_log.as_bytes()));
+    }
+    files.push((&target_file_path, &script_dat));
+    Transaction::prepare(&files)
+        .await
+        .map_err(transaction_failed)?
+        .commit()
+        .await
+        .map_err(transaction_failed)?;
+    *LAST_SPOILER.lock().unwrap() = None;
+    let written = spoiler_log.is_some().then_some(SPOILER_LOG_FILE_NAME);
+    remove_stale_spoiler_logs(&format!("{}/data", install_directory), written).await;
+    Ok(())
+}
+
+/// Returns how many backups were removed.
+#[tauri::command]
+pub async fn prune_backups(install_directory: String, keep: usize) -> Result<usize, AppError> {
+    let _lock = INSTALL_LOCK.lock().await;
+    BackupStore::new(&install_directory)
+        .prune(keep)
+        .await
+        .map_err(backup_failed)
+}
+
+fn backup_failed(err: anyhow::Error) -> AppError {
+    AppError::BackupFailed {
+        details: format!("{:#}", err),
+    }
+}
+
+fn backup_read_failed(err: anyhow::Error) -> AppError {
+    AppError::BackupReadFailed {
+        details: format!("{:#}", err),
+    }
+}
+
+/// The vanilla script.dat of the backups, or the `script.dat.bak` of older versions.
+async fn read_vanilla(install_directory: &str) -> Option<Vec<u8>> {
+    match BackupStore::new(install_directory).vanilla().await {
+        Ok(Some(working)) => return Some(working),
+        Ok(None) => {}
+        Err(e) => error!("{:?}", e),
+    }
+    read_valid_file_or_null(&format!("{}/data/script.dat.bak", install_directory)).await
+}
+
+/// The vanilla script.dat, taken from the backups or backed up on first use if `create_backup`.
+/// script.dat of an unknown version is taken only if `allow_unknown_version`, and backed up only
+/// if `confirm_unknown_vanilla` too, since it may be randomized or modified already.
+async fn read_working(
+    install_directory: &str,
+    create_backup: bool,
+    allow_unknown_version: bool,
+    confirm_unknown_vanilla: bool,
+) -> Result<Vec<u8>, AppError> {
+    let store = BackupStore::new(install_directory);
+    if let Ok(Some(working)) = store.vanilla().await {
+        return Ok(working);
+    }
+    let legacy_file_path = format!("{}/data/script.dat.bak", install_directory);
+    let working = match read_valid_file_or_null(&legacy_file_path).await {
+        Some(working) => working,
+        None => {
+            let target_file_path = format!("{}/data/script.dat", install_directory);
+            let Some(working) = read_file(&target_file_path).await.ok() else {
+                return Err(AppError::InstallDirectoryNotFound);
+            };
+            if !is_valid_script_dat(&working) {
+                if !allow_unknown_version {
+                    return Err(AppError::UnknownScriptVersion);
+                }
+                check_script_dat(&working).map_err(|e| AppError::InvalidScript {
+                    details: e.to_string(),
+                })?;
+                if create_backup && !confirm_unknown_vanilla {
+                    return Err(AppError::UnknownVanillaNotConfirmed);
+                }
+            }
+            working
+        }
+    };
+    if create_backup {
+        store
+            .store_vanilla(&working, confirm_unknown_vanilla)
+            .await
+            .map_err(backup_failed)?;
+    }
+    Ok(working)
 }
 
 async fn read_valid_file_or_null(path: &str) -> Option<Vec<u8>> {
@@ -308,20 +883,3 @@ async fn read_valid_file_or_null(path: &str) -> Option<Vec<u8>> {
     }
     Some(working)
 }
-
-async fn write_valid_script_dat(path: &str, script_dat: &[u8]) -> io::Result<()> {
-    write_file(path, script_dat).await?;
-    if cfg!(debug_assertions) {
-        let Ok(outputed) = read_file(path).await else {
-            panic!();
-        };
-        if !is_valid_script_dat(&outputed) {
-            panic!();
-        }
-    }
-    Ok(())
-}
-
-async fn write_spoiler_log(path: &str, spoiler_log: &SpoilerLog) -> io::Result<()> {
-    write_file(path, spoiler_log.to_string().as_bytes()).await
-}
@@ -0,0 +1,249 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, bail, Result};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sha3::Digest;
+use tokio::fs;
+
+use crate::script::file::scriptconverter::{check_script_dat, is_valid_script_dat};
+
+use super::transaction::write_atomically;
+
+const DIRECTORY_NAME: &str = "lmorandomizer_backups";
+const INDEX_FILE_NAME: &str = "index.json";
+const VANILLA_FILE_NAME: &str = "vanilla.dat";
+/// Randomized outputs kept by `apply` and `apply_patch`.
+pub const KEEP_COUNT: usize = 5;
+
+/// A randomized script.dat kept in the backup store.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Backup {
+    pub id: String,
+    /// Empty for a script.dat applied from a patch, which doesn't carry them.
+    pub seed: String,
+    pub settings: String,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Index {
+    /// SHA3-256 of the vanilla script.dat.
+    vanilla_hash: Option<String>,
+    /// The vanilla script.dat is not a known version and was accepted on request because it
+    /// passed the structural validation.
+    #[serde(default)]
+    vanilla_unknown_version: bool,
+    /// Oldest first.
+    backups: Vec<Backup>,
+}
+
+fn hash(script_dat: &[u8]) -> String {
+    hex::encode(sha3::Sha3_256::digest(script_dat))
+}
+
+/// `<install>/data/lmorandomizer_backups`, holding the vanilla script.dat and the last
+/// randomized ones with their spoiler logs.
+pub struct BackupStore {
+    directory: PathBuf,
+}
+
+impl BackupStore {
+    pub fn new(install_directory: &str) -> Self {
+        let directory = Path::new(install_directory)
+            .join("data")
+            .join(DIRECTORY_NAME);
+        Self { directory }
+    }
+
+    fn script_dat_path(&self, id: &str) -> PathBuf {
+        self.directory.join(format!("{}.dat", id))
+    }
+
+    fn spoiler_log_path(&self, id: &str) -> PathBuf {
+        self.directory.join(format!("{}.txt", id))
+    }
+
+    async fn read_index(&self) -> Result<Index> {
+        match fs::read_to_string(self.directory.join(INDEX_FILE_NAME)).await {
+            Ok(text) => Ok(serde_json::from_str(&text)?),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Index::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn write_index(&self, index: &Index) -> Result<()> {
+        fs::create_dir_all(&self.directory).await?;
+        let text = serde_json::to_string_pretty(index)?;
+        let path = self.directory.join(INDEX_FILE_NAME);
+        write_atomically(&path, text.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// The vanilla script.dat, if it is stored and still matches its hash.
+    pub async fn vanilla(&self) -> Result<Option<Vec<u8>>> {
+        let index = self.read_index().await?;
+        let Some(expected) = index.vanilla_hash else {
+            return Ok(None);
+        };
+        let script_dat = match fs::read(self.directory.join(VANILLA_FILE_NAME)).await {
+            Ok(script_dat) => script_dat,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let original = if index.vanilla_unknown_version {
+            check_script_dat(&script_dat).is_ok()
+        } else {
+            is_valid_script_dat(&script_dat)
+        };
+        if hash(&script_dat) != expected || !original {
+            warn!("The vanilla script.dat in the backups is broken");
+            return Ok(None);
+        }
+        Ok(Some(script_dat))
+    }
+
+    /// Only a known version is stored unless `allow_unknown_version`, which is recorded.
+    pub async fn store_vanilla(
+        &self,
+        script_dat: &[u8],
+        allow_unknown_version: bool,
+    ) -> Result<()> {
+        let unknown_version = !is_valid_script_dat(script_dat);
+        if unknown_version {
+            if !allow_unknown_version {
+                bail!("script.dat is not a known version");
+            }
+            check_script_dat(script_dat)?;
+        }
+        fs::create_dir_all(&self.directory).await?;
+        write_atomically(&self.directory.join(VANILLA_FILE_NAME), script_dat).await?;
+        let mut index = self.read_index().await?;
+        index.vanilla_hash = Some(hash(script_dat));
+        index.vanilla_unknown_version = unknown_version;
+        self.write_index(&index).await
+    }
+
+    /// Newest first.
+    pub async fn list(&self) -> Result<Vec<Backup>> {
+        let mut backups = self.read_index().await?.backups;
+        backups.reverse();
+        Ok(backups)
+    }
+
+    /// Keeps a randomized script.dat, then drops the oldest ones beyond `keep`.
+    /// The spoiler log is `None` if it must not be readable from the install.
+    pub async fn add(
+        &self,
+        seed: &str,
+        settings: &str,
+        script_dat: &[u8],
+        spoiler_log: Option<&str>,
+        keep: usize,
+    ) -> Result<Backup> {
+        let mut index = self.read_index().await?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        let mut id = timestamp.to_string();
+        for i in 1.. {
+            if !index.backups.iter().any(|x| x.id == id) {
+                break;
+            }
+            id = format!("{}-{}", timestamp, i);
+        }
+        fs::create_dir_all(&self.directory).await?;
+        fs::write(self.script_dat_path(&id), script_dat).await?;
+        if let Some(spoiler_log) = spoiler_log {
+            fs::write(self.spoiler_log_path(&id), spoiler_log).await?;
+        }
+        let backup = Backup {
+            id,
+            seed: seed.to_owned(),
+            settings: settings.to_owned(),
+            timestamp,
+        };
+        index.backups.push(backup.clone());
+        self.write_index(&index).await?;
+        self.prune(keep).await?;
+        Ok(backup)
+    }
+
+    /// The script.dat and spoiler log of a backup.
+    pub async fn read(&self, id: &str) -> Result<(Vec<u8>, Option<String>)> {
+        let index = self.read_index().await?;
+        if !index.backups.iter().any(|x| x.id == id) {
+            return Err(anyhow!("backup not found: {}", id));
+        }
+        let script_dat = fs::read(self.script_dat_path(id)).await?;
+        let spoiler_log = match fs::read_to_string(self.spoiler_log_path(id)).await {
+            Ok(spoiler_log) => Some(spoiler_log),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        Ok((script_dat, spoiler_log))
+    }
+
+    /// Removes all but the newest `keep` backups. The vanilla script.dat is always kept.
+    /// Returns how many were removed.
+    pub async fn prune(&self, keep: usize) -> Result<usize> {
+        let mut index = self.read_index().await?;
+        let count = index.backups.len().saturating_sub(keep);
+        let removed: Vec<_> = index.backups.drain(..count).collect();
+        self.write_index(&index).await?;
+        for backup in &removed {
+            for path in [
+                self.script_dat_path(&backup.id),
+                self.spoiler_log_path(&backup.id),
+            ] {
+                match fs::remove_file(path).await {
+                    Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+                    _ => {}
+                }
+            }
+        }
+        Ok(removed.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_backup_store() -> Result<()> {
+        let install_directory =
+            std::env::temp_dir().join(format!("lmorandomizer_test_backups_{}", std::process::id()));
+        let store = BackupStore::new(install_directory.to_str().unwrap());
+        assert!(store.vanilla().await?.is_none());
+        assert!(store.list().await?.is_empty());
+        assert!(store.store_vanilla(&[0], false).await.is_err());
+        assert!(store.vanilla().await?.is_none());
+
+        for i in 0..3 {
+            let seed = i.to_string();
+            let spoiler_log = (i != 2).then_some(seed.as_str());
+            let backup = store.add(&seed, "R--", &[i], spoiler_log, 2).await?;
+            assert_eq!(backup.seed, seed);
+        }
+        let backups = store.list().await?;
+        let seeds: Vec<_> = backups.iter().map(|x| x.seed.as_str()).collect();
+        assert_eq!(seeds, ["2", "1"]);
+        assert_eq!(
+            store.read(&backups[1].id).await?,
+            (vec![1], Some("1".to_owned()))
+        );
+        assert_eq!(store.read(&backups[0].id).await?, (vec![2], None));
+        assert_eq!(store.prune(1).await?, 1);
+        assert!(store.read(&backups[1].id).await.is_err());
+        assert_eq!(store.list().await?.len(), 1);
+
+        std::fs::remove_dir_all(&install_directory)?;
+        Ok(())
+    }
+}
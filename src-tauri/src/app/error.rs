@@ -0,0 +1,155 @@
+use std::fmt;
+
+/// Errors of the commands. The frontend tells them apart by `code`.
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "code", rename_all = "camelCase")]
+pub enum AppError {
+    InstallDirectoryNotFound,
+    /// script.dat is neither a known version nor structurally valid.
+    InvalidScript {
+        details: String,
+    },
+    /// script.dat is not a known version, and unknown versions were not allowed.
+    UnknownScriptVersion,
+    /// script.dat of an unknown version would become the vanilla backup, which every later seed
+    /// and restore builds on, and that was not confirmed.
+    UnknownVanillaNotConfirmed,
+    BackupNotFound,
+    BackupBroken,
+    BackupFailed {
+        details: String,
+    },
+    BackupReadFailed {
+        details: String,
+    },
+    UnknownBackup {
+        id: String,
+    },
+    NotRandomized,
+    GameStructureReadFailed {
+        details: String,
+    },
+    GenerationFailed {
+        details: String,
+    },
+    Cancelled,
+    SpoilerNotFound,
+    SpoilerEncryptionFailed {
+        details: String,
+    },
+    SpoilerDecryptionFailed {
+        details: String,
+    },
+    VerificationFailed {
+        details: String,
+    },
+    PatchReadFailed {
+        details: String,
+    },
+    PatchFailed {
+        details: String,
+    },
+    WriteFailed {
+        path: String,
+        details: String,
+    },
+    StoreFailed {
+        details: String,
+    },
+    PresetFailed {
+        details: String,
+    },
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InstallDirectoryNotFound => {
+                write!(f, "Unable to find La-Mulana install directory.")
+            }
+            Self::InvalidScript { details } => write!(
+                f,
+                "Valid script is not found. Please re-install La-Mulana.\n{}",
+                details
+            ),
+            Self::UnknownScriptVersion => write!(
+                f,
+                "script.dat is not a known version. Please re-install La-Mulana, or allow unknown versions if it is a translation or a community patch."
+            ),
+            Self::UnknownVanillaNotConfirmed => write!(
+                f,
+                "script.dat is not a known version, so it is kept as the original only on confirmation. Please make sure that it is neither randomized nor modified, then confirm it as the original."
+            ),
+            Self::BackupNotFound => write!(f, "Backup is not found. Please randomize first."),
+            Self::BackupBroken => write!(f, "Backup is broken. Please re-install La-Mulana."),
+            Self::BackupFailed { details } => write!(f, "Failed to backup script.dat: {}", details),
+            Self::BackupReadFailed { details } => {
+                write!(f, "Failed to read the backups: {}", details)
+            }
+            Self::UnknownBackup { id } => write!(f, "Backup {} is not found.", id),
+            Self::NotRandomized => write!(f, "script.dat is not randomized."),
+            Self::GameStructureReadFailed { details } => {
+                write!(f, "Failed to read game structure files: {}", details)
+            }
+            Self::GenerationFailed { details } => write!(f, "Randomization failed: {}", details),
+            Self::Cancelled => write!(f, "Cancelled."),
+            Self::SpoilerNotFound => write!(f, "No spoiler yet. Please randomize first."),
+            Self::SpoilerEncryptionFailed { details } => {
+                write!(f, "Failed to encrypt the spoiler log: {}", details)
+            }
+            Self::SpoilerDecryptionFailed { details } => {
+                write!(f, "Failed to decrypt the spoiler log: {}", details)
+            }
+            Self::VerificationFailed { details } => write!(f, "Verification failed: {}", details),
+            Self::PatchReadFailed { details } => {
+                write!(f, "Unable to read the patch file: {}", details)
+            }
+            Self::PatchFailed { details } => write!(f, "Patch failed: {}", details),
+            Self::WriteFailed { path, details } => {
+                write!(f, "Failed to write {}: {}", path, details)
+            }
+            Self::StoreFailed { details } => write!(f, "Failed to save settings: {}", details),
+            Self::PresetFailed { details } => write!(f, "Preset operation failed: {}", details),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<tauri_plugin_store::Error> for AppError {
+    fn from(err: tauri_plugin_store::Error) -> Self {
+        Self::StoreFailed {
+            details: err.to_string(),
+        }
+    }
+}
+
+impl AppError {
+    pub fn write_failed(path: &str, err: impl fmt::Display) -> Self {
+        Self::WriteFailed {
+            path: path.to_owned(),
+            details: err.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        let json = serde_json::to_value(AppError::BackupBroken).unwrap();
+        assert_eq!(json, serde_json::json!({ "code": "backupBroken" }));
+        let err = AppError::write_failed("a/script.dat", "denied");
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            serde_json::json!({
+                "code": "writeFailed",
+                "path": "a/script.dat",
+                "details": "denied",
+            })
+        );
+        assert_eq!(err.to_string(), "Failed to write a/script.dat: denied");
+    }
+}
@@ -0,0 +1,256 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tokio::fs;
+
+use crate::script::file::scriptconverter::is_valid_script_dat;
+
+use super::backups::BackupStore;
+
+const GAME_DIRECTORY_NAME: &str = "La-Mulana";
+/// Where Windows installers put the game in a Wine prefix.
+const PROGRAM_DIRECTORIES: [&str; 3] = ["Program Files", "Program Files (x86)", "GOG Games"];
+
+/// Where a candidate was found. Earlier ones rank higher.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Source {
+    User,
+    Steam,
+    Proton,
+    Wine,
+    Lutris,
+    Bottles,
+    Gog,
+}
+
+/// Why a candidate is considered an install. Earlier ones rank higher.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Evidence {
+    /// data/script.dat is one of the known versions.
+    KnownScript,
+    /// data/script.dat is modified, but the vanilla one is backed up.
+    Backup,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Candidate {
+    pub path: String,
+    pub source: Source,
+    pub evidence: Evidence,
+}
+
+async fn sub_directories(directory: &Path) -> Vec<PathBuf> {
+    let Ok(mut entries) = fs::read_dir(directory).await else {
+        return Vec::new();
+    };
+    let mut directories = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if entry.file_type().await.is_ok_and(|x| x.is_dir()) {
+            directories.push(entry.path());
+        }
+    }
+    directories.sort();
+    directories
+}
+
+fn in_prefix(prefix: &Path) -> impl Iterator<Item = PathBuf> + '_ {
+    PROGRAM_DIRECTORIES
+        .iter()
+        .map(move |x| prefix.join("drive_c").join(x).join(GAME_DIRECTORY_NAME))
+}
+
+/// Steam library folders, read from the `"path"` entries of libraryfolders.vdf.
+async fn steam_libraries(steam_root: &Path) -> Vec<PathBuf> {
+    let mut libraries = vec![steam_root.to_owned()];
+    let vdf_path = steam_root.join("steamapps").join("libraryfolders.vdf");
+    let Ok(vdf) = fs::read_to_string(vdf_path).await else {
+        return libraries;
+    };
+    for line in vdf.lines() {
+        let mut tokens = line.split('"').skip(1).step_by(2);
+        if let (Some("path"), Some(path)) = (tokens.next(), tokens.next()) {
+            libraries.push(PathBuf::from(path.replace("\\\\", "\\")));
+        }
+    }
+    libraries
+}
+
+/// The usual install locations of the native game, Steam, Proton, Wine, Lutris and Bottles.
+async fn default_locations(
+    home: &Path,
+    data_home: &Path,
+    wine_prefix: Option<PathBuf>,
+) -> Vec<(Source, PathBuf)> {
+    let mut locations = Vec::new();
+    let steam_roots = [
+        home.join(".steam").join("steam"),
+        data_home.join("Steam"),
+        home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+    ];
+    for steam_root in steam_roots {
+        for library in steam_libraries(&steam_root).await {
+            let steamapps = library.join("steamapps");
+            let common = steamapps.join("common").join(GAME_DIRECTORY_NAME);
+            locations.push((Source::Steam, common));
+            for app in sub_directories(&steamapps.join("compatdata")).await {
+                let prefix = app.join("pfx");
+                locations.extend(in_prefix(&prefix).map(|x| (Source::Proton, x)));
+            }
+        }
+    }
+    let wine_prefix = wine_prefix.unwrap_or_else(|| home.join(".wine"));
+    locations.extend(in_prefix(&wine_prefix).map(|x| (Source::Wine, x)));
+    for game in sub_directories(&home.join("Games")).await {
+        locations.extend(in_prefix(&game).map(|x| (Source::Lutris, x)));
+        locations.push((Source::Lutris, game.join(GAME_DIRECTORY_NAME)));
+        locations.push((Source::Lutris, game));
+    }
+    let bottles_roots = [
+        data_home.join("bottles").join("bottles"),
+        home.join(".var/app/com.usebottles.bottles/data/bottles/bottles"),
+    ];
+    for bottles_root in bottles_roots {
+        for bottle in sub_directories(&bottles_root).await {
+            locations.extend(in_prefix(&bottle).map(|x| (Source::Bottles, x)));
+        }
+    }
+    locations.push((
+        Source::Gog,
+        home.join("GOG Games").join(GAME_DIRECTORY_NAME),
+    ));
+    locations
+}
+
+/// A search root may be the install itself, hold it, or be a Wine prefix or a directory of them.
+async fn user_locations(search_root: &Path) -> Vec<PathBuf> {
+    let mut locations = vec![
+        search_root.to_owned(),
+        search_root.join(GAME_DIRECTORY_NAME),
+    ];
+    locations.extend(in_prefix(search_root));
+    for child in sub_directories(search_root).await {
+        locations.push(child.join(GAME_DIRECTORY_NAME));
+        locations.extend(in_prefix(&child));
+        locations.extend(in_prefix(&child.join("pfx")));
+    }
+    locations
+}
+
+async fn evidence(directory: &Path) -> Option<Evidence> {
+    let data = directory.join("data");
+    let script_dat = fs::read(data.join("script.dat")).await.ok()?;
+    if is_valid_script_dat(&script_dat) {
+        return Some(Evidence::KnownScript);
+    }
+    let store = BackupStore::new(directory.to_str()?);
+    if let Ok(Some(_)) = store.vanilla().await {
+        return Some(Evidence::Backup);
+    }
+    // Backup of older versions
+    let backup = fs::read(data.join("script.dat.bak")).await.ok()?;
+    is_valid_script_dat(&backup).then_some(Evidence::Backup)
+}
+
+async fn locations(search_roots: &[String]) -> Vec<(Source, PathBuf)> {
+    let mut locations = Vec::new();
+    for search_root in search_roots {
+        let found = user_locations(Path::new(search_root)).await;
+        locations.extend(found.into_iter().map(|x| (Source::User, x)));
+    }
+    if cfg!(target_os = "linux") {
+        if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+            let data_home = std::env::var_os("XDG_DATA_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join(".local").join("share"));
+            let wine_prefix = std::env::var_os("WINEPREFIX").map(PathBuf::from);
+            locations.extend(default_locations(&home, &data_home, wine_prefix).await);
+        }
+    }
+    locations
+}
+
+/// Looks for installs in `search_roots` and, on Linux, in the usual locations.
+/// The best candidates come first.
+pub async fn detect_install_directories(search_roots: &[String]) -> Vec<Candidate> {
+    let mut seen = Vec::new();
+    let mut candidates = Vec::new();
+    for (source, location) in locations(search_roots).await {
+        let Ok(path) = fs::canonicalize(&location).await else {
+            continue;
+        };
+        if seen.contains(&path) {
+            continue;
+        }
+        seen.push(path.clone());
+        let Some(evidence) = evidence(&path).await else {
+            continue;
+        };
+        candidates.push(Candidate {
+            path: path.to_string_lossy().into_owned(),
+            source,
+            evidence,
+        });
+    }
+    candidates.sort_by_key(|x| (x.evidence, x.source));
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_default_locations() -> std::io::Result<()> {
+        let home = std::env::temp_dir().join(format!(
+            "lmorandomizer_test_install_detection_{}",
+            std::process::id()
+        ));
+        let data_home = home.join(".local/share");
+        let steamapps = data_home.join("Steam/steamapps");
+        fs::create_dir_all(steamapps.join("compatdata/1234/pfx")).await?;
+        fs::write(
+            steamapps.join("libraryfolders.vdf"),
+            "\"libraryfolders\"\n{\n\t\"0\"\n\t{\n\t\t\"path\"\t\t\"/mnt/games\"\n\t}\n}\n",
+        )
+        .await?;
+        fs::create_dir_all(home.join("Games/la-mulana")).await?;
+        fs::create_dir_all(data_home.join("bottles/bottles/Games")).await?;
+
+        let locations = default_locations(&home, &data_home, None).await;
+        let has = |source, path: PathBuf| locations.contains(&(source, path));
+        let game = GAME_DIRECTORY_NAME;
+        assert!(has(Source::Steam, steamapps.join("common").join(game)));
+        assert!(has(
+            Source::Steam,
+            PathBuf::from("/mnt/games/steamapps/common").join(game)
+        ));
+        assert!(has(
+            Source::Proton,
+            steamapps
+                .join("compatdata/1234/pfx/drive_c/Program Files (x86)")
+                .join(game)
+        ));
+        assert!(has(
+            Source::Wine,
+            home.join(".wine/drive_c/Program Files").join(game)
+        ));
+        assert!(has(
+            Source::Lutris,
+            home.join("Games/la-mulana/drive_c/GOG Games").join(game)
+        ));
+        assert!(has(
+            Source::Bottles,
+            data_home
+                .join("bottles/bottles/Games/drive_c/Program Files")
+                .join(game)
+        ));
+
+        assert!(evidence(&home.join("Games/la-mulana")).await.is_none());
+
+        fs::remove_dir_all(&home).await?;
+        Ok(())
+    }
+}
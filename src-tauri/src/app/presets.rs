@@ -0,0 +1,177 @@
+use std::sync::LazyLock;
+
+use anyhow::{anyhow, bail, Result};
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::randomizer::RandomizeOptions;
+
+const BUILT_IN_PRESETS_YAML: &str = include_str!("../../res/presets.yml");
+
+static BUILT_IN_PRESETS: LazyLock<Vec<Preset>> = LazyLock::new(|| {
+    let mut presets: Vec<Preset> = serde_yaml::from_str(BUILT_IN_PRESETS_YAML).unwrap();
+    for preset in &mut presets {
+        preset.built_in = true;
+    }
+    presets
+});
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Preset {
+    pub name: String,
+    /// Built-in presets can't be changed. Always false when imported.
+    #[serde(default, skip_deserializing)]
+    pub built_in: bool,
+    #[serde(deserialize_with = "options_with_defaults")]
+    pub options: RandomizeOptions,
+}
+
+/// Options missing from a preset take their default values, so that presets saved before an
+/// option was added still load.
+fn options_with_defaults<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<RandomizeOptions, D::Error> {
+    let Value::Object(input) = Value::deserialize(deserializer)? else {
+        return Err(D::Error::custom("options must be an object"));
+    };
+    let mut options =
+        serde_json::to_value(RandomizeOptions::default()).map_err(D::Error::custom)?;
+    if let Value::Object(options) = &mut options {
+        options.extend(input);
+    }
+    serde_json::from_value(options).map_err(D::Error::custom)
+}
+
+/// The presets saved by the user. Built-in presets come first and are never stored.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Presets(Vec<Preset>);
+
+fn check_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        bail!("preset name is empty");
+    }
+    Ok(())
+}
+
+impl Presets {
+    pub fn all(&self) -> Vec<Preset> {
+        BUILT_IN_PRESETS.iter().chain(&self.0).cloned().collect()
+    }
+
+    pub fn get(&self, name: &str) -> Result<Preset> {
+        BUILT_IN_PRESETS
+            .iter()
+            .chain(&self.0)
+            .find(|x| x.name == name)
+            .cloned()
+            .ok_or_else(|| anyhow!("preset not found: {}", name))
+    }
+
+    fn is_built_in(name: &str) -> bool {
+        BUILT_IN_PRESETS.iter().any(|x| x.name == name)
+    }
+
+    fn position(&self, name: &str) -> Result<usize> {
+        if Self::is_built_in(name) {
+            bail!("built-in preset can't be changed: {}", name);
+        }
+        self.0
+            .iter()
+            .position(|x| x.name == name)
+            .ok_or_else(|| anyhow!("preset not found: {}", name))
+    }
+
+    /// Overwrites the preset of the same name if any.
+    pub fn save(&mut self, name: String, options: RandomizeOptions) -> Result<()> {
+        check_name(&name)?;
+        if Self::is_built_in(&name) {
+            bail!("built-in preset can't be changed: {}", name);
+        }
+        let preset = Preset {
+            name,
+            built_in: false,
+            options,
+        };
+        match self.0.iter_mut().find(|x| x.name == preset.name) {
+            Some(old) => *old = preset,
+            None => self.0.push(preset),
+        }
+        Ok(())
+    }
+
+    pub fn rename(&mut self, name: &str, new_name: String) -> Result<()> {
+        check_name(&new_name)?;
+        let index = self.position(name)?;
+        if name != new_name && self.get(&new_name).is_ok() {
+            bail!("preset already exists: {}", new_name);
+        }
+        self.0[index].name = new_name;
+        Ok(())
+    }
+
+    pub fn delete(&mut self, name: &str) -> Result<()> {
+        let index = self.position(name)?;
+        self.0.remove(index);
+        Ok(())
+    }
+
+    pub fn export(&self, name: &str) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.get(name)?)?)
+    }
+
+    /// Adds a preset exported by `export`. A taken name gets a number appended.
+    pub fn import(&mut self, json: &str) -> Result<Preset> {
+        let mut preset: Preset = serde_json::from_str(json)?;
+        check_name(&preset.name)?;
+        let base_name = preset.name.clone();
+        for i in 2.. {
+            if self.get(&preset.name).is_err() {
+                break;
+            }
+            preset.name = format!("{} ({})", base_name, i);
+        }
+        self.0.push(preset.clone());
+        Ok(preset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets() -> Result<()> {
+        let mut presets = Presets::default();
+        let built_in_count = presets.all().len();
+        assert!(presets.all().iter().all(|x| x.built_in));
+        assert_eq!(presets.get("Hard")?.options.settings_code(), "RG-");
+
+        let options = RandomizeOptions::from_settings_code("R-A")?;
+        assert!(presets.save("Normal".to_owned(), options.clone()).is_err());
+        presets.save("Race".to_owned(), options.clone())?;
+        presets.rename("Race", "Race S3".to_owned())?;
+        assert!(presets.rename("Race S3", "Easy".to_owned()).is_err());
+        assert!(presets.delete("Easy").is_err());
+
+        let json = presets.export("Race S3")?;
+        let imported = presets.import(&json)?;
+        assert_eq!(imported.name, "Race S3 (2)");
+        assert_eq!(imported.options, options);
+        let imported = presets.import(&presets.export("Easy")?)?;
+        assert!(!imported.built_in);
+        assert_eq!(imported.name, "Easy (2)");
+
+        // Options missing from older files take their defaults.
+        let imported =
+            presets.import(r#"{ "name": "Old", "options": { "needGlitches": true } }"#)?;
+        assert_eq!(imported.options.settings_code(), "RG-");
+        let options = r#"{ "needGlitches": true }"#;
+        assert!(serde_json::from_str::<RandomizeOptions>(options).is_err());
+
+        presets.delete("Race S3")?;
+        assert_eq!(presets.all().len(), built_in_count + 3);
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use log::{error, info};
+use tokio::{
+    fs::{self, File},
+    io::{self, AsyncWriteExt},
+};
+
+struct Entry {
+    path: PathBuf,
+    temp_path: PathBuf,
+    /// `None` if the file did not exist.
+    previous: Option<Vec<u8>>,
+    committed: bool,
+}
+
+/// Replaces several files together.
+/// The new contents are written to temporary files next to the destinations and synced first,
+/// so a failed or interrupted write never leaves a partial file behind.
+pub struct Transaction {
+    entries: Vec<Entry>,
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_owned();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+async fn read_previous(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    match fs::read(path).await {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+async fn write_synced(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = File::create(path).await?;
+    file.write_all(contents).await?;
+    file.sync_all().await
+}
+
+/// Makes the renames in the directory durable. Not possible on Windows.
+async fn sync_parent(path: &Path) -> io::Result<()> {
+    if cfg!(unix) {
+        if let Some(parent) = path.parent().filter(|x| !x.as_os_str().is_empty()) {
+            File::open(parent).await?.sync_all().await?;
+        }
+    }
+    Ok(())
+}
+
+async fn replace(path: &Path, temp_path: &Path) -> io::Result<()> {
+    fs::rename(temp_path, path).await?;
+    sync_parent(path).await
+}
+
+/// Replaces a single file the same way, without keeping the previous contents.
+pub async fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let temp_path = temp_path(path);
+    if let Err(e) = write_synced(&temp_path, contents).await {
+        let _ = fs::remove_file(&temp_path).await;
+        return Err(e);
+    }
+    replace(path, &temp_path).await
+}
+
+impl Transaction {
+    /// Writes the temporary files. Nothing is replaced yet.
+    /// The error carries the path of the file that failed.
+    pub async fn prepare(files: &[(&str, &[u8])]) -> Result<Self, (PathBuf, io::Error)> {
+        let mut transaction = Self {
+            entries: Vec::with_capacity(files.len()),
+        };
+        for &(path, contents) in files {
+            let path = PathBuf::from(path);
+            let temp_path = temp_path(&path);
+            let previous = read_previous(&path).await.map_err(|e| (path.clone(), e))?;
+            info!("Writing file: {}", temp_path.display());
+            transaction.entries.push(Entry {
+                path,
+                temp_path,
+                previous,
+                committed: false,
+            });
+            // Pushed first so that the drop cleans up a partial temporary file too.
+            let entry = transaction.entries.last().unwrap();
+            if let Err(e) = write_synced(&entry.temp_path, contents).await {
+                return Err((entry.path.clone(), e));
+            }
+        }
+        Ok(transaction)
+    }
+
+    /// Renames the temporary files over the destinations in order.
+    /// If any rename fails, the files already replaced are rolled back.
+    pub async fn commit(&mut self) -> Result<(), (PathBuf, io::Error)> {
+        for i in 0..self.entries.len() {
+            let entry = &self.entries[i];
+            info!("Replacing file: {}", entry.path.display());
+            if let Err(e) = replace(&entry.path, &entry.temp_path).await {
+                let path = entry.path.clone();
+                if let Err(e) = self.rollback_committed().await {
+                    error!("Rollback failed: {:?}", e);
+                }
+                return Err((path, e));
+            }
+            self.entries[i].committed = true;
+        }
+        Ok(())
+    }
+
+    /// Puts back what the destinations held before the commit.
+    pub async fn rollback(mut self) -> io::Result<()> {
+        self.rollback_committed().await
+    }
+
+    async fn rollback_committed(&mut self) -> io::Result<()> {
+        for entry in self.entries.iter_mut().rev().filter(|x| x.committed) {
+            info!("Rolling back file: {}", entry.path.display());
+            match &entry.previous {
+                Some(previous) => {
+                    write_synced(&entry.temp_path, previous).await?;
+                    replace(&entry.path, &entry.temp_path).await?;
+                }
+                None => fs::remove_file(&entry.path).await?,
+            }
+            entry.committed = false;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        for entry in &self.entries {
+            if let Err(e) = std::fs::remove_file(&entry.temp_path) {
+                if e.kind() != ErrorKind::NotFound {
+                    error!("{:?}", e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_transaction() -> io::Result<()> {
+        let directory = std::env::temp_dir().join(format!(
+            "lmorandomizer_test_transaction_{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&directory).await?;
+        let a = directory.join("a.dat");
+        let b = directory.join("b.txt");
+        let (a_path, b_path) = (a.to_str().unwrap(), b.to_str().unwrap());
+        fs::write(&a, "old").await?;
+
+        let mut transaction = Transaction::prepare(&[(a_path, b"new"), (b_path, b"log")])
+            .await
+            .map_err(|(_, e)| e)?;
+        assert_eq!(fs::read(&a).await?, b"old");
+        transaction.commit().await.map_err(|(_, e)| e)?;
+        assert_eq!(fs::read(&a).await?, b"new");
+        assert_eq!(fs::read(&b).await?, b"log");
+        transaction.rollback().await?;
+        assert_eq!(fs::read(&a).await?, b"old");
+        assert!(!b.exists());
+
+        // The second rename fails, so the first file is rolled back.
+        let mut transaction = Transaction::prepare(&[(a_path, b"new"), (b_path, b"log")])
+            .await
+            .map_err(|(_, e)| e)?;
+        fs::remove_file(temp_path(&b)).await?;
+        let (path, _) = transaction.commit().await.unwrap_err();
+        assert_eq!(path, b);
+        assert_eq!(fs::read(&a).await?, b"old");
+        assert!(!b.exists());
+        drop(transaction);
+        assert!(!temp_path(&a).exists() && !temp_path(&b).exists());
+
+        write_atomically(&a, b"single").await?;
+        assert_eq!(fs::read(&a).await?, b"single");
+        assert!(!temp_path(&a).exists());
+
+        fs::remove_dir_all(&directory).await?;
+        Ok(())
+    }
+}
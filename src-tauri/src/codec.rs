@@ -8,20 +8,67 @@ mod randomizer;
 #[cfg(not(test))]
 mod script;
 
+#[cfg(not(test))]
+const USAGE: &str = "\
+Usage: lmocodec.exe [decode|encode] [input file] [output file]
+       lmocodec.exe export [script.dat] [output .yaml/.json]
+       lmocodec.exe import [input .yaml/.json] [script.dat]
+       lmocodec.exe diff [script.dat] [script.dat]
+       lmocodec.exe flags [script.dat] [flag...]
+       lmocodec.exe graph [script.dat] [output .dot/.json]
+       lmocodec.exe validate [script.dat]
+       lmocodec.exe stats [settings code] [first seed] [seed count] [output .json/.csv]
+       lmocodec.exe placements [settings code] [vanilla script.dat] [script.dat]
+       lmocodec.exe verify [vanilla script.dat] [script.dat] [spoilerlog.txt]
+       lmocodec.exe make-patch [vanilla script.dat] [script.dat] [patch file]
+       lmocodec.exe apply-patch [vanilla script.dat] [patch file] [output file]
+       lmocodec.exe decrypt-spoiler [spoilerlog.enc] [password] [output file (optional)]";
+
 #[cfg(not(test))]
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() <= 3 {
-        eprintln!("Usage: lmocodec.exe [decode|encode] [input file] [output file]",);
+    if args.len() <= 2 {
+        eprintln!("{}", USAGE);
         std::process::exit(1);
     }
     let mode = &args[1];
-    if mode != "decode" && mode != "encode" {
-        eprintln!("Invalid mode: {}", mode);
+    let result = match mode.as_str() {
+        "decode" | "encode" if args.len() > 3 => {
+            code(mode, &args[2], &args[3]);
+            Ok(())
+        }
+        "export" if args.len() > 3 => export(&args[2], &args[3]),
+        "import" if args.len() > 3 => import(&args[2], &args[3]),
+        "diff" if args.len() > 3 => diff(&args[2], &args[3]),
+        "flags" => flags(&args[2], &args[3..]),
+        "graph" if args.len() > 3 => graph(&args[2], &args[3]),
+        "validate" => validate(&args[2]),
+        "stats" if args.len() > 5 => stats(&args[2], &args[3], &args[4], &args[5]),
+        "placements" if args.len() > 4 => placements(&args[2], &args[3], &args[4]),
+        "verify" if args.len() > 4 => verify(&args[2], &args[3], &args[4]),
+        "make-patch" if args.len() > 4 => make_patch(&args[2], &args[3], &args[4]),
+        "apply-patch" if args.len() > 4 => apply_patch(&args[2], &args[3], &args[4]),
+        "decrypt-spoiler" if args.len() > 3 => {
+            decrypt_spoiler(&args[2], &args[3], args.get(4).map(|x| x.as_str()))
+        }
+        "decode" | "encode" | "export" | "import" | "diff" | "graph" | "stats" | "placements"
+        | "verify" | "make-patch" | "apply-patch" | "decrypt-spoiler" => {
+            eprintln!("{}", USAGE);
+            std::process::exit(1);
+        }
+        _ => {
+            eprintln!("Invalid mode: {}", mode);
+            std::process::exit(1);
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("{:#}", err);
         std::process::exit(1);
     }
-    let input_file_path = &args[2];
-    let output_file_path = &args[3];
+}
+
+#[cfg(not(test))]
+fn code(mode: &str, input_file_path: &str, output_file_path: &str) {
     if mode == "decode" {
         let input_file = std::fs::read(input_file_path).unwrap();
         let output = script::file::dat::cipher_to_text(&input_file);
@@ -32,3 +79,262 @@ fn main() {
         std::fs::write(output_file_path, output).unwrap();
     }
 }
+
+#[cfg(not(test))]
+fn export(script_dat_path: &str, output_file_path: &str) -> anyhow::Result<()> {
+    use script::file::{
+        scriptconverter::{build_script_dat, parse_script_dat},
+        structured::{export_script, import_script, Format},
+    };
+
+    let format = Format::from_path(output_file_path)?;
+    let script_dat = std::fs::read(script_dat_path)?;
+    let script = parse_script_dat(&script_dat)?;
+    let output = export_script(&script, format)?;
+    // Refuse to write an export that would not import back to the same file.
+    if build_script_dat(&import_script(&output, format)?) != script_dat {
+        anyhow::bail!("the export does not round-trip to {}", script_dat_path);
+    }
+    std::fs::write(output_file_path, output)?;
+    Ok(())
+}
+
+#[cfg(not(test))]
+fn import(input_file_path: &str, script_dat_path: &str) -> anyhow::Result<()> {
+    use script::file::{
+        scriptconverter::{build_script_dat, is_valid_script_dat},
+        structured::{import_script, Format},
+    };
+
+    let format = Format::from_path(input_file_path)?;
+    let input = std::fs::read_to_string(input_file_path)?;
+    let script_dat = build_script_dat(&import_script(&input, format)?);
+    if is_valid_script_dat(&script_dat) {
+        println!("Known version.");
+    }
+    std::fs::write(script_dat_path, script_dat)?;
+    Ok(())
+}
+
+#[cfg(not(test))]
+fn diff(old_script_dat_path: &str, new_script_dat_path: &str) -> anyhow::Result<()> {
+    use script::file::scriptconverter::parse_script_dat;
+
+    let old = parse_script_dat(&std::fs::read(old_script_dat_path)?)?;
+    let new = parse_script_dat(&std::fs::read(new_script_dat_path)?)?;
+    let diffs = script::diff::diff(&old, &new);
+    if diffs.is_empty() {
+        println!("No differences.");
+    }
+    for diff in &diffs {
+        println!("{}", diff);
+    }
+    Ok(())
+}
+
+#[cfg(not(test))]
+fn flags(script_dat_path: &str, flags: &[String]) -> anyhow::Result<()> {
+    use script::data::flags::FlagIndex;
+
+    let script_dat = std::fs::read(script_dat_path)?;
+    let script = script::file::scriptconverter::parse_script_dat(&script_dat)?;
+    let index = FlagIndex::new(&script)?;
+    if flags.is_empty() {
+        print!("{}", index);
+    }
+    for flag in flags {
+        print!("{}", index.entry(flag.parse()?));
+    }
+    Ok(())
+}
+
+#[cfg(not(test))]
+fn read_game_structure_files() -> anyhow::Result<dataset::game_structure::GameStructureFiles> {
+    use dataset::game_structure::{
+        field_logic_number, GameStructureFiles, EVENTS_FILE_PATH, FIELD_FILE_PATHS,
+    };
+
+    let fields = FIELD_FILE_PATHS
+        .into_iter()
+        .map(|path| Ok((field_logic_number(path), std::fs::read_to_string(path)?)))
+        .collect::<anyhow::Result<_>>()?;
+    let events = std::fs::read_to_string(EVENTS_FILE_PATH)?;
+    GameStructureFiles::new(fields, events)
+}
+
+#[cfg(not(test))]
+fn graph(script_dat_path: &str, output_file_path: &str) -> anyhow::Result<()> {
+    use dataset::game_structure::GameStructure;
+    use script::screen_graph::{screen_graph, to_dot};
+
+    let script_dat = std::fs::read(script_dat_path)?;
+    let script = script::file::scriptconverter::parse_script_dat(&script_dat)?;
+    let game_structure = GameStructure::new(read_game_structure_files()?)?;
+    let graph = screen_graph(&script, &game_structure)?;
+    let extension = output_file_path.rsplit_once('.').map(|x| x.1);
+    let output = match extension {
+        Some("dot" | "gv") => to_dot(&graph),
+        Some("json") => serde_json::to_string_pretty(&graph)?,
+        _ => anyhow::bail!(
+            "unknown format: {} (expected .dot or .json)",
+            output_file_path
+        ),
+    };
+    std::fs::write(output_file_path, output)?;
+    for name in &graph.unplaced_spots {
+        eprintln!("spot not found on any screen: {}", name);
+    }
+    Ok(())
+}
+
+#[cfg(not(test))]
+fn stats(
+    settings_code: &str,
+    first_seed: &str,
+    seed_count: &str,
+    output_file_path: &str,
+) -> anyhow::Result<()> {
+    use dataset::game_structure::GameStructure;
+    use randomizer::stats::{seed_stats, to_csv};
+
+    let options = randomizer::RandomizeOptions::from_settings_code(settings_code)?;
+    let first_seed: u64 = first_seed.parse()?;
+    let last_seed = first_seed
+        .checked_add(seed_count.parse()?)
+        .ok_or_else(|| anyhow::anyhow!("too many seeds after {}", first_seed))?;
+    let seeds = first_seed..last_seed;
+    let game_structure = GameStructure::new(read_game_structure_files()?)?;
+    let stats = seed_stats(&game_structure, &options, seeds)?;
+    match output_file_path.rsplit_once('.') {
+        Some((_, "json")) => {
+            std::fs::write(output_file_path, serde_json::to_string_pretty(&stats)?)?;
+        }
+        // stats.csv is written as stats.seeds.csv, stats.items.csv and stats.fields.csv.
+        Some((stem, "csv")) => {
+            for (table, csv) in to_csv(&stats) {
+                std::fs::write(format!("{}.{}.csv", stem, table), csv)?;
+            }
+        }
+        _ => anyhow::bail!(
+            "unknown format: {} (expected .json or .csv)",
+            output_file_path
+        ),
+    }
+    println!(
+        "{} seeds: {:.2} spheres, {:.1} tries, {:.0} ms on average",
+        stats.seeds.len(),
+        stats.average_spheres,
+        stats.average_tries,
+        stats.average_millis
+    );
+    Ok(())
+}
+
+/// The settings select the spots, so they must be the ones the seed was made with.
+#[cfg(not(test))]
+fn placements(
+    settings_code: &str,
+    vanilla_script_dat_path: &str,
+    script_dat_path: &str,
+) -> anyhow::Result<()> {
+    let options = randomizer::RandomizeOptions::from_settings_code(settings_code)?;
+    let vanilla_script_dat = std::fs::read(vanilla_script_dat_path)?;
+    let script_dat = std::fs::read(script_dat_path)?;
+    let game_structure = dataset::game_structure::GameStructure::new(read_game_structure_files()?)?;
+    let storage =
+        randomizer::read_placements(&vanilla_script_dat, &script_dat, &game_structure, &options)?;
+    print!("{}", storage);
+    Ok(())
+}
+
+#[cfg(not(test))]
+fn verify(
+    vanilla_script_dat_path: &str,
+    script_dat_path: &str,
+    spoiler_log_path: &str,
+) -> anyhow::Result<()> {
+    let vanilla_script_dat = std::fs::read(vanilla_script_dat_path)?;
+    let script_dat = std::fs::read(script_dat_path)?;
+    let spoiler_log = std::fs::read_to_string(spoiler_log_path)?;
+    // The settings are taken from the seed banner at the top of the spoiler log.
+    let Some(code) = spoiler_log
+        .lines()
+        .next()
+        .filter(|x| x.starts_with("SEED "))
+        .and_then(|x| x.split(' ').nth(2))
+    else {
+        anyhow::bail!("seed banner not found in {}", spoiler_log_path)
+    };
+    let options = randomizer::RandomizeOptions::from_settings_code(code)?;
+    let game_structure = dataset::game_structure::GameStructure::new(read_game_structure_files()?)?;
+    randomizer::verify_script_dat(
+        &vanilla_script_dat,
+        &script_dat,
+        &game_structure,
+        &options,
+        &spoiler_log,
+    )?;
+    println!("OK");
+    Ok(())
+}
+
+#[cfg(not(test))]
+fn make_patch(
+    vanilla_script_dat_path: &str,
+    script_dat_path: &str,
+    patch_path: &str,
+) -> anyhow::Result<()> {
+    let vanilla_script_dat = std::fs::read(vanilla_script_dat_path)?;
+    let script_dat = std::fs::read(script_dat_path)?;
+    let patch = script::file::patch::make_patch(&vanilla_script_dat, &script_dat)?;
+    std::fs::write(patch_path, patch)?;
+    Ok(())
+}
+
+#[cfg(not(test))]
+fn apply_patch(
+    vanilla_script_dat_path: &str,
+    patch_path: &str,
+    output_file_path: &str,
+) -> anyhow::Result<()> {
+    let vanilla_script_dat = std::fs::read(vanilla_script_dat_path)?;
+    let patch = std::fs::read(patch_path)?;
+    let script_dat = script::file::patch::apply_patch(&vanilla_script_dat, &patch)?;
+    std::fs::write(output_file_path, script_dat)?;
+    Ok(())
+}
+
+/// Prints the spoiler log unless an output file is given.
+#[cfg(not(test))]
+fn decrypt_spoiler(
+    encrypted_path: &str,
+    password: &str,
+    output_file_path: Option<&str>,
+) -> anyhow::Result<()> {
+    let encrypted = std::fs::read(encrypted_path)?;
+    let spoiler_log = randomizer::spoiler_encryption::decrypt(&encrypted, password)?;
+    match output_file_path {
+        Some(output_file_path) => std::fs::write(output_file_path, spoiler_log)?,
+        None => print!("{}", spoiler_log),
+    }
+    Ok(())
+}
+
+#[cfg(not(test))]
+fn validate(script_dat_path: &str) -> anyhow::Result<()> {
+    let script_dat = std::fs::read(script_dat_path)?;
+    if script::file::scriptconverter::is_valid_script_dat(&script_dat) {
+        println!("Known version.");
+        return Ok(());
+    }
+    let script = script::file::scriptconverter::parse_script_dat(&script_dat)?;
+    let issues = script::validation::validate(&script);
+    if issues.is_empty() {
+        println!("Unknown version, structurally valid.");
+        return Ok(());
+    }
+    for issue in &issues {
+        println!("{}", issue);
+    }
+    anyhow::bail!("{} issues found", issues.len())
+}
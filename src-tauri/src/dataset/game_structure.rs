@@ -7,7 +7,8 @@ use vec1::Vec1;
 use crate::{
     dataset::spot::SpotName,
     script::enums::{
-        ChestItem, Equipment, FieldNumber, MainWeapon, Rom, Seal, ShopItem, SubWeapon, TalkItem,
+        to_pascal_case, ChestItem, Equipment, FieldNumber, MainWeapon, Rom, Seal, ShopItem,
+        SubWeapon, TalkItem,
     },
 };
 
@@ -16,6 +17,34 @@ use super::spot::{
     SealSpot, ShopSpot, SubWeaponSpot, TalkSpot,
 };
 
+pub const FIELD_FILE_PATHS: [&str; 19] = [
+    "res/00_Surface.yml",
+    "res/01_Gate_of_Guidance.yml",
+    "res/02_Mausoleum_of_the_Giants.yml",
+    "res/03_Temple_of_the_Sun.yml",
+    "res/04_Spring_in_the_Sky.yml",
+    "res/05_Inferno_Cavern.yml",
+    "res/06_Chamber_of_Extinction.yml",
+    "res/07_Twin_Labyrinths_Left.yml",
+    "res/08_Endless_Corridor.yml",
+    "res/09_Shrine_of_the_Mother.yml",
+    "res/11_Gate_of_Illusion.yml",
+    "res/12_Graveyard_of_the_Giants.yml",
+    "res/13_Temple_of_Moonlight.yml",
+    "res/14_Tower_of_the_Goddess.yml",
+    "res/15_Tower_of_Ruin.yml",
+    "res/16_Chamber_of_Birth.yml",
+    "res/17_Twin_Labyrinths_Right.yml",
+    "res/18_Dimensional_Corridor.yml",
+    "res/19_True_Shrine_of_the_Mother.yml",
+];
+pub const EVENTS_FILE_PATH: &str = "res/events.yml";
+
+/// The field logic number is the prefix of the file name.
+pub fn field_logic_number(file_path: &str) -> u8 {
+    file_path[4..6].parse().unwrap()
+}
+
 pub struct GameStructureFiles {
     pub fields: Vec<(FieldNumber, FieldYaml)>,
     pub events: EventsYaml,
@@ -99,14 +128,6 @@ fn parse_event_requirements(items: BTreeMap<String, Vec<String>>) -> Result<Vec<
         .collect()
 }
 
-fn to_pascal_case(camel_case: &str) -> String {
-    camel_case[0..1]
-        .to_uppercase()
-        .chars()
-        .chain(camel_case[1..].chars())
-        .collect()
-}
-
 #[derive(Clone, Debug)]
 pub struct Event {
     pub name: SpotName,
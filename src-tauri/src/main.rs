@@ -6,7 +6,6 @@ mod dataset;
 pub mod randomizer;
 mod script;
 
-use log::LevelFilter;
 use tauri::Config;
 
 fn main() {
@@ -15,11 +14,7 @@ fn main() {
     let version = version.as_ref().unwrap();
     app.windows[0].title = format!("La-Mulana Original Randomizer v{version}",);
     tauri::Builder::default()
-        .plugin(
-            tauri_plugin_log::Builder::new()
-                .level_for("html5ever", LevelFilter::Warn)
-                .build(),
-        )
+        .plugin(tauri_plugin_log::Builder::new().build())
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_shell::init())
         .invoke_handler(tauri::generate_handler![
@@ -27,12 +22,27 @@ fn main() {
             app::ready,
             app::set_seed,
             app::set_install_directory,
+            app::detect_install_directories,
             app::set_easy_mode,
             app::set_shuffle_secret_roms,
             app::set_need_glitches,
             app::set_absolutely_shuffle,
+            app::list_presets,
+            app::save_preset,
+            app::rename_preset,
+            app::delete_preset,
+            app::export_preset,
+            app::import_preset,
             app::apply,
+            app::cancel,
+            app::spoiler,
+            app::decrypt_spoiler_log,
+            app::make_patch,
+            app::apply_patch,
             app::restore,
+            app::list_backups,
+            app::restore_backup,
+            app::prune_backups,
         ])
         .run(context)
         .expect("error while running tauri application");
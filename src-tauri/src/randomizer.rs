@@ -1,27 +1,39 @@
+pub mod progress;
 mod randomize_items;
 mod spoiler;
+pub mod spoiler_encryption;
 mod spoiler_log;
+pub mod spoiler_view;
+pub mod stats;
 pub mod storage;
+pub mod verify;
 
 use std::mem::take;
 
-use anyhow::Result;
-use log::trace;
+use anyhow::{anyhow, bail, Result};
+use log::{trace, warn};
+use progress::{Progress, Stage};
 use randomize_items::randomize_items;
+use sha3::Digest;
+use spoiler::sphere::is_reachable;
 pub use spoiler_log::SpoilerLog;
 use storage::{create_source::create_source, Storage};
 
 use crate::{
     dataset::{
-        game_structure::{GameStructure, GameStructureFiles},
-        NIGHT_SURFACE_CHEST_COUNT, NIGHT_SURFACE_SEAL_COUNT, NIGHT_SURFACE_SUB_WEAPON_COUNT,
+        game_structure::GameStructure, spot::ShopSpot, NIGHT_SURFACE_CHEST_COUNT,
+        NIGHT_SURFACE_SEAL_COUNT, NIGHT_SURFACE_SUB_WEAPON_COUNT,
         TRUE_SHRINE_OF_THE_MOTHER_SEAL_COUNT,
     },
     script::{
-        data::script::Script,
-        editor::add_starting_items::add_starting_items,
+        data::{flags::FlagAllocator, script::Script},
+        decompiler::decompile,
+        editor::{
+            add_starting_items::add_starting_items, apply_seed_banner, find_shop_talk_number,
+            shop_description_line_limit,
+        },
         enums::Rom,
-        file::scriptconverter::{build_script_dat, read_script_dat},
+        file::scriptconverter::{build_script_dat, parse_script_dat, read_script_dat},
     },
 };
 
@@ -31,13 +43,13 @@ pub fn assert_eq_elem_count(source: &Storage, script: &Script) {
     let sub_weapon_count = source.sub_weapons.len() + NIGHT_SURFACE_SUB_WEAPON_COUNT;
     debug_assert_eq!(sub_weapon_count, script.sub_weapons().count());
     let chest_count = source.chests.len() + NIGHT_SURFACE_CHEST_COUNT;
-    debug_assert_eq!(chest_count, script.chests().count());
+    debug_assert_eq!(chest_count, script.chests().map_or(0, |x| x.count()));
     let seal_count =
         source.seals.len() + TRUE_SHRINE_OF_THE_MOTHER_SEAL_COUNT + NIGHT_SURFACE_SEAL_COUNT;
     debug_assert_eq!(seal_count, script.seals().count());
 }
 
-#[derive(serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RandomizeOptions {
     pub seed: String,
@@ -46,17 +58,90 @@ pub struct RandomizeOptions {
     pub absolutely_shuffle: bool,
 }
 
+impl Default for RandomizeOptions {
+    fn default() -> Self {
+        Self {
+            seed: String::new(),
+            shuffle_secret_roms: true,
+            need_glitches: false,
+            absolutely_shuffle: false,
+        }
+    }
+}
+
+impl RandomizeOptions {
+    /// One letter per setting, `-` when it is off.
+    pub fn settings_code(&self) -> String {
+        [
+            (self.shuffle_secret_roms, 'R'),
+            (self.need_glitches, 'G'),
+            (self.absolutely_shuffle, 'A'),
+        ]
+        .into_iter()
+        .map(|(on, code)| if on { code } else { '-' })
+        .collect()
+    }
+
+    /// The seed itself cannot be recovered from the code.
+    pub fn from_settings_code(code: &str) -> Result<Self> {
+        let flags: Option<Vec<_>> = code
+            .chars()
+            .zip("RGA".chars())
+            .map(|(x, on)| match x {
+                '-' => Some(false),
+                _ => (x == on).then_some(true),
+            })
+            .collect();
+        let (3, Some(&[shuffle_secret_roms, need_glitches, absolutely_shuffle])) =
+            (code.len(), flags.as_deref())
+        else {
+            bail!("invalid settings code: {}", code)
+        };
+        Ok(Self {
+            seed: String::new(),
+            shuffle_secret_roms,
+            need_glitches,
+            absolutely_shuffle,
+        })
+    }
+}
+
+/// Short enough for the 22 columns of a talk.
+/// Shown in-game and at the top of the spoiler log so that players can compare their seeds.
+pub fn seed_banner(options: &RandomizeOptions, spoiler_log: &SpoilerLog) -> String {
+    let hash = sha3::Sha3_256::digest(spoiler_log.to_string());
+    format!(
+        "SEED {} {}",
+        hex::encode_upper(&hash[..4]),
+        options.settings_code()
+    )
+}
+
+/// The shop that shows the seed banner.
+/// It is reachable with no items, so players can check the seed right after starting.
+fn banner_shop(source: &Storage) -> Result<&ShopSpot> {
+    source
+        .shops
+        .iter()
+        .map(|x| &x.spot)
+        .find(|x| is_reachable(x.requirements(), &Default::default(), 0))
+        .ok_or_else(|| anyhow!("no shop is reachable at the start"))
+}
+
+/// Fails with `progress::Cancelled` if `progress` cancels it.
 pub fn randomize(
     script_dat: &[u8],
-    game_structure_files: GameStructureFiles,
+    game_structure: &GameStructure,
     options: &RandomizeOptions,
+    progress: &dyn Progress,
 ) -> Result<(Vec<u8>, SpoilerLog)> {
+    progress.report(Stage::ReadScript);
     let start = std::time::Instant::now();
     let mut script = read_script_dat(script_dat)?;
     trace!("Read script.dat in {:?}", start.elapsed());
 
-    let game_structure = GameStructure::new(game_structure_files)?;
-    let source = create_source(&game_structure, options)?;
+    progress.report(Stage::BuildStructure);
+    let source = create_source(game_structure, options)?;
 
     if cfg!(debug_assertions) {
         let start = std::time::Instant::now();
@@ -65,11 +150,19 @@ pub fn randomize(
     }
 
     let start = std::time::Instant::now();
-    let spoiler_log = randomize_items(&mut script, &source, options)?;
+    let banner_shop_talk_number = find_shop_talk_number(&script, banner_shop(&source)?)?;
+    let line_limit = shop_description_line_limit(&script)?;
+    let spoiler_log = randomize_items(&mut script, &source, options, progress)?.to_owned();
+    let banner = seed_banner(options, &spoiler_log);
+    if !apply_seed_banner(&mut script, banner_shop_talk_number, line_limit, &banner)? {
+        warn!("No item description of the shop has room for the seed banner");
+    }
     if false {
+        let mut flags = FlagAllocator::new(&script)?;
         let worlds = take(&mut script.worlds);
         script.worlds = add_starting_items(
             worlds,
+            &mut flags,
             &[
                 // crate::script::data::items::Equipment::Boots,
                 // crate::script::data::items::Equipment::Feather,
@@ -81,7 +174,7 @@ pub fn randomize(
             &[
                 // crate::script::data::items::SubWeapon::Pistol
             ],
-        );
+        )?;
     }
     trace!("Randomized items in {:?}", start.elapsed());
 
@@ -89,5 +182,86 @@ pub fn randomize(
     let dat = build_script_dat(&script);
     trace!("Built script.dat in {:?}", start.elapsed());
 
-    Ok((dat, spoiler_log.to_owned()))
+    Ok((dat, spoiler_log))
+}
+
+/// Reads the placements back from a randomized script.dat.
+/// The options select the spots in the same way as when the seed was made.
+pub fn read_placements(
+    vanilla_script_dat: &[u8],
+    script_dat: &[u8],
+    game_structure: &GameStructure,
+    options: &RandomizeOptions,
+) -> Result<Storage> {
+    let vanilla = read_script_dat(vanilla_script_dat)?;
+    let target = parse_script_dat(script_dat)?;
+    let source = create_source(game_structure, options)?;
+    decompile(&vanilla, &target, &source)
+}
+
+/// Verifies a randomized script.dat independently of the placements computed by `randomize`.
+pub fn verify_script_dat(
+    vanilla_script_dat: &[u8],
+    script_dat: &[u8],
+    game_structure: &GameStructure,
+    options: &RandomizeOptions,
+    spoiler_log: &str,
+) -> Result<()> {
+    let storage = read_placements(vanilla_script_dat, script_dat, game_structure, options)?;
+    verify::verify(&storage, options, spoiler_log)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::{read_game_structure_files_debug, read_script_dat_debug},
+        script::enums::FieldNumber,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_banner_shop() -> Result<()> {
+        let game_structure_files = read_game_structure_files_debug().await?;
+        let game_structure = GameStructure::new(game_structure_files)?;
+        for code in ["R--", "RGA", "-G-"] {
+            let source = create_source(
+                &game_structure,
+                &RandomizeOptions::from_settings_code(code)?,
+            )?;
+            let spot = banner_shop(&source)?;
+            assert_eq!(spot.field_number(), FieldNumber::Surface);
+            assert!(spot.requirements().is_none());
+            assert_eq!(spot.name().get(), "gameMaster, glyphReader, ruinsRam8K");
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "needs the vanilla script.dat in LMO_SCRIPT_DAT"]
+    async fn test_randomize_seed_banner() -> Result<()> {
+        let vanilla = read_script_dat_debug()?;
+        let game_structure = GameStructure::new(read_game_structure_files_debug().await?)?;
+        let options = RandomizeOptions {
+            seed: "test".to_owned(),
+            ..Default::default()
+        };
+        let (randomized, spoiler_log) = randomize(&vanilla, &game_structure, &options, &())?;
+
+        let source = create_source(&game_structure, &options)?;
+        let vanilla = read_script_dat(&vanilla)?;
+        let talk_number = find_shop_talk_number(&vanilla, banner_shop(&source)?)?;
+        let line_limit = shop_description_line_limit(&vanilla)?;
+        let script = read_script_dat(&randomized)?;
+        let banner = seed_banner(&options, &spoiler_log);
+        let descriptions: Vec<_> = (1..=3)
+            .map(|i| script.talks[talk_number as usize + i].to_string())
+            .collect();
+        let found = descriptions
+            .iter()
+            .find(|x| x.ends_with(&format!("\n{}", banner)));
+        let description = found.expect("seed banner not found");
+        assert!(description.lines().count() <= line_limit, "{}", description);
+        Ok(())
+    }
 }
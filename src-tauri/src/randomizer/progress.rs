@@ -0,0 +1,45 @@
+use std::fmt;
+
+use serde::Serialize;
+
+/// Stages of a generation, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(tag = "stage", rename_all = "camelCase")]
+pub enum Stage {
+    ReadScript,
+    BuildStructure,
+    /// Reported after each batch of failed shuffles.
+    Shuffle {
+        tries: usize,
+    },
+    ApplyStorage,
+    /// Not reported by `randomize`, the caller writes the files.
+    Write,
+}
+
+/// Receives the stages of `randomize` and can stop it between shuffle batches.
+pub trait Progress: Sync {
+    fn report(&self, stage: Stage);
+    fn is_cancelled(&self) -> bool;
+}
+
+/// Reports nothing and never cancels.
+impl Progress for () {
+    fn report(&self, _stage: Stage) {}
+
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// The error of a generation stopped by `Progress::is_cancelled`.
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
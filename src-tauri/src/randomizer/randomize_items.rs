@@ -10,6 +10,7 @@ use crate::{
 };
 
 use super::{
+    progress::{Cancelled, Progress, Stage},
     spoiler::{make_rng, spoiler},
     spoiler_log::{CheckpointRef, SpoilerLogRef},
     storage::{item::StrategyFlag, Storage},
@@ -20,15 +21,17 @@ pub fn randomize_items<'a>(
     script: &mut Script,
     source: &'a Storage,
     options: &RandomizeOptions,
+    progress: &dyn Progress,
 ) -> Result<SpoilerLogRef<'a>> {
     let start = std::time::Instant::now();
     assert_unique(source);
     trace!("Assertion in {:?}", start.elapsed());
 
     let start = std::time::Instant::now();
-    let (shuffled, spoiler_log) = shuffle(source, options);
+    let (shuffled, spoiler_log) = shuffle(source, options, progress)?;
     trace!("Randomized items in {:?}", start.elapsed());
 
+    progress.report(Stage::ApplyStorage);
     let start = std::time::Instant::now();
     assert_unique(&shuffled);
     apply_storage(script, &shuffled)?;
@@ -92,7 +95,8 @@ fn random_spoiler<'a>(
     rng: &mut impl Rng,
     source: &'a Storage,
     options: &RandomizeOptions,
-) -> SpoilerLogRef<'a> {
+    progress: &dyn Progress,
+) -> Result<(SpoilerLogRef<'a>, usize), Cancelled> {
     let start = std::time::Instant::now();
     let items = &Items::new(source);
     let spots = &Spots::new(source);
@@ -117,26 +121,47 @@ fn random_spoiler<'a>(
     let thread_count = std::thread::available_parallelism().unwrap().get();
     std::thread::scope(|scope| {
         for i in 0..100000 {
+            if progress.is_cancelled() {
+                info!("Shuffle was cancelled after {} tries", i * thread_count);
+                return Err(Cancelled);
+            }
             let handles: Vec<_> = (0..thread_count)
                 .map(|_| rng.next_u64())
                 .map(|seed| scope.spawn(move || spoiler(seed, options, items, spots)))
                 .collect();
             let Some(spoiler_log) = handles.into_iter().filter_map(|h| h.join().unwrap()).next()
             else {
+                progress.report(Stage::Shuffle {
+                    tries: (i + 1) * thread_count,
+                });
                 continue;
             };
-            info!("Shuffle was tried: {} times", (i + 1) * thread_count);
-            return spoiler_log;
+            let tries = (i + 1) * thread_count;
+            info!("Shuffle was tried: {} times", tries);
+            return Ok((spoiler_log, tries));
         }
         unreachable!();
     })
 }
 
-fn shuffle<'a>(source: &'a Storage, options: &RandomizeOptions) -> (Storage, SpoilerLogRef<'a>) {
+/// The spoiler log of the seed, and how many shuffles were tried to get it.
+pub fn seeded_spoiler<'a>(
+    source: &'a Storage,
+    options: &RandomizeOptions,
+) -> (SpoilerLogRef<'a>, usize) {
+    let mut rng = make_rng(&options.seed);
+    random_spoiler(&mut rng, source, options, &()).unwrap()
+}
+
+fn shuffle<'a>(
+    source: &'a Storage,
+    options: &RandomizeOptions,
+    progress: &dyn Progress,
+) -> Result<(Storage, SpoilerLogRef<'a>), Cancelled> {
     let mut rng = make_rng(&options.seed);
-    let spoiler_log = random_spoiler(&mut rng, source, options);
+    let (spoiler_log, _) = random_spoiler(&mut rng, source, options, progress)?;
     let storage = create_shuffled_storage(source, &spoiler_log);
-    (storage, spoiler_log)
+    Ok((storage, spoiler_log))
 }
 
 fn assert_unique(storage: &Storage) {
@@ -181,8 +206,17 @@ mod tests {
     use sha3::Digest;
 
     use crate::{
-        app::read_game_structure_files_debug, dataset::game_structure::GameStructure,
-        randomizer::storage::create_source::create_source,
+        app::{read_game_structure_files_debug, read_script_dat_debug},
+        dataset::game_structure::GameStructure,
+        randomizer::{
+            randomize, seed_banner, storage::create_source::create_source, verify::verify,
+            verify_script_dat,
+        },
+        script::{
+            data::talk::Talk,
+            decompiler::decompile,
+            file::scriptconverter::{parse_script_dat, read_script_dat},
+        },
     };
 
     use super::*;
@@ -198,7 +232,7 @@ mod tests {
             absolutely_shuffle: false,
         };
         let source = create_source(&game_structure, &opts)?;
-        let (shuffled, spoiler_log) = shuffle(&source, &opts);
+        let (shuffled, spoiler_log) = shuffle(&source, &opts, &())?;
 
         let shuffled_str = format!("{:?}", shuffled);
         let shuffled_hash = hex::encode(sha3::Sha3_512::digest(shuffled_str));
@@ -225,7 +259,7 @@ mod tests {
                 absolutely_shuffle: false,
             };
             let source = create_source(&game_structure, &opts)?;
-            let (_, spoiler_log) = shuffle(&source, &opts);
+            let (_, spoiler_log) = shuffle(&source, &opts, &())?;
             assert_eq!(
                 spoiler_log.count_checkpoints(),
                 source.all_items().count() + source.events.len()
@@ -234,4 +268,107 @@ mod tests {
 
         Ok(())
     }
+
+    struct AlwaysCancelled;
+
+    impl Progress for AlwaysCancelled {
+        fn report(&self, stage: Stage) {
+            panic!("{:?}", stage);
+        }
+
+        fn is_cancelled(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shuffle_cancel() -> Result<()> {
+        let game_structure_files = read_game_structure_files_debug().await?;
+        let game_structure = GameStructure::new(game_structure_files)?;
+        let opts = RandomizeOptions {
+            seed: "test".to_owned(),
+            shuffle_secret_roms: true,
+            need_glitches: false,
+            absolutely_shuffle: false,
+        };
+        let source = create_source(&game_structure, &opts)?;
+        assert!(shuffle(&source, &opts, &AlwaysCancelled).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_seed_banner() -> Result<()> {
+        let game_structure_files = read_game_structure_files_debug().await?;
+        let game_structure = GameStructure::new(game_structure_files)?;
+        let opts = RandomizeOptions {
+            seed: "test".to_owned(),
+            shuffle_secret_roms: true,
+            need_glitches: false,
+            absolutely_shuffle: false,
+        };
+        let source = create_source(&game_structure, &opts)?;
+        let (_, spoiler_log) = shuffle(&source, &opts, &())?;
+
+        let banner = seed_banner(&opts, &spoiler_log.to_owned());
+        assert_eq!(banner, seed_banner(&opts, &spoiler_log.to_owned()));
+        assert!(banner.starts_with("SEED ") && banner.ends_with(" R--"));
+        assert!(banner.chars().count() <= 22);
+        assert_eq!(Talk::from_text(&banner).to_string(), banner);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_shuffled() -> Result<()> {
+        let game_structure_files = read_game_structure_files_debug().await?;
+        let game_structure = GameStructure::new(game_structure_files)?;
+        let opts = RandomizeOptions {
+            seed: "test".to_owned(),
+            shuffle_secret_roms: true,
+            need_glitches: false,
+            absolutely_shuffle: false,
+        };
+        let source = create_source(&game_structure, &opts)?;
+        let (mut shuffled, spoiler_log) = shuffle(&source, &opts, &())?;
+        let spoiler_log = spoiler_log.to_owned().to_string();
+        verify(&shuffled, &opts, &spoiler_log)?;
+
+        let mut chests = shuffled.chests.values_mut();
+        let (a, b) = (chests.next().unwrap(), chests.next().unwrap());
+        std::mem::swap(&mut a.item, &mut b.item);
+        assert!(verify(&shuffled, &opts, &spoiler_log).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "needs the vanilla script.dat in LMO_SCRIPT_DAT"]
+    async fn test_decompile_randomized() -> Result<()> {
+        let vanilla = read_script_dat_debug()?;
+        let game_structure_files = read_game_structure_files_debug().await?;
+        let game_structure = GameStructure::new(game_structure_files)?;
+        let opts = RandomizeOptions {
+            seed: "test".to_owned(),
+            shuffle_secret_roms: true,
+            need_glitches: false,
+            absolutely_shuffle: false,
+        };
+        let source = create_source(&game_structure, &opts)?;
+        let (shuffled, spoiler_log) = shuffle(&source, &opts, &())?;
+        let (randomized, _) = randomize(&vanilla, &game_structure, &opts, &())?;
+
+        let vanilla_script = read_script_dat(&vanilla)?;
+        let decompiled = decompile(&vanilla_script, &parse_script_dat(&randomized)?, &source)?;
+        let sorted_lines = |storage: &Storage| {
+            let mut lines: Vec<_> = storage.to_string().lines().map(str::to_owned).collect();
+            lines.sort();
+            lines
+        };
+        assert_eq!(sorted_lines(&decompiled), sorted_lines(&shuffled));
+
+        let spoiler_log = spoiler_log.to_owned().to_string();
+        verify_script_dat(&vanilla, &randomized, &game_structure, &opts, &spoiler_log)?;
+
+        Ok(())
+    }
 }
@@ -1,6 +1,6 @@
 pub mod items;
 mod items_pool;
-mod sphere;
+pub mod sphere;
 pub mod spots;
 
 use std::{
@@ -26,7 +26,8 @@ use super::{
 
 use {items::Items, sphere::sphere, spots::Spots};
 
-static GLITCH: LazyLock<StrategyFlag> = LazyLock::new(|| StrategyFlag::new("option:glitch".into()));
+pub static GLITCH: LazyLock<StrategyFlag> =
+    LazyLock::new(|| StrategyFlag::new("option:glitch".into()));
 
 pub fn make_rng<H: Hash>(seed: H) -> Xoshiro256PlusPlus {
     Seeder::from(seed).make_rng()
@@ -25,7 +25,7 @@ pub struct ShopItemDisplay<'a> {
     pub name: &'a StrategyFlag,
 }
 
-fn is_reachable(
+pub fn is_reachable(
     requirements: Option<&AnyOfAllRequirements>,
     current_strategy_flags: &HashSet<&str>,
     sacred_orb_count: u8,
@@ -0,0 +1,86 @@
+use anyhow::{anyhow, bail, Result};
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{Aead, Payload},
+    ChaCha20Poly1305, Key, KeyInit, Nonce,
+};
+use rand::Rng;
+
+const MAGIC: &[u8; 12] = b"LMOSPOILER2\0";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+/// The Poly1305 tag at the end of the ciphertext.
+const TAG_LEN: usize = 16;
+
+/// Argon2id with its default cost, which slows down guessing short passwords.
+fn derive_cipher(password: &str, salt: &[u8]) -> Result<ChaCha20Poly1305> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("key derivation failed: {}", e))?;
+    Ok(ChaCha20Poly1305::new(&key))
+}
+
+/// Encrypts a spoiler log so that only the holder of the password can read it.
+/// The output is `MAGIC | salt | nonce | ciphertext | tag`, with the header authenticated too.
+pub fn encrypt(spoiler_log: &str, password: &str) -> Result<Vec<u8>> {
+    if password.is_empty() {
+        bail!("password is empty");
+    }
+    let mut rng = rand::thread_rng();
+    let salt: [u8; SALT_LEN] = rng.gen();
+    let nonce: [u8; NONCE_LEN] = rng.gen();
+    let cipher = derive_cipher(password, &salt)?;
+
+    let header = [MAGIC.as_slice(), &salt, &nonce].concat();
+    let payload = Payload {
+        msg: spoiler_log.as_bytes(),
+        aad: &header,
+    };
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), payload)
+        .map_err(|_| anyhow!("encryption failed"))?;
+    Ok([header, ciphertext].concat())
+}
+
+pub fn decrypt(data: &[u8], password: &str) -> Result<String> {
+    let header_len = MAGIC.len() + SALT_LEN + NONCE_LEN;
+    if data.len() < header_len + TAG_LEN || !data.starts_with(MAGIC) {
+        bail!("not an encrypted spoiler log");
+    }
+    let (header, ciphertext) = data.split_at(header_len);
+    let salt = &header[MAGIC.len()..][..SALT_LEN];
+    let nonce = &header[MAGIC.len() + SALT_LEN..];
+    let payload = Payload {
+        msg: ciphertext,
+        aad: header,
+    };
+    let plaintext = derive_cipher(password, salt)?
+        .decrypt(Nonce::from_slice(nonce), payload)
+        .map_err(|_| anyhow!("wrong password or corrupted file"))?;
+    Ok(String::from_utf8(plaintext)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encryption() -> Result<()> {
+        let spoiler_log = "SEED 0123ABCD R--\n\n[Sphere 0]\nSurface_Chest(shellHorn) = feather\n";
+        let encrypted = encrypt(spoiler_log, "organizer key")?;
+        assert!(!encrypted.windows(b"feather".len()).any(|x| x == b"feather"));
+        assert_eq!(decrypt(&encrypted, "organizer key")?, spoiler_log);
+        assert!(decrypt(&encrypted, "organizer").is_err());
+
+        let mut tampered = encrypted.clone();
+        tampered[MAGIC.len() + SALT_LEN + NONCE_LEN] ^= 1;
+        assert!(decrypt(&tampered, "organizer key").is_err());
+        let mut tampered = encrypted.clone();
+        tampered[MAGIC.len()] ^= 1;
+        assert!(decrypt(&tampered, "organizer key").is_err());
+        assert!(decrypt(spoiler_log.as_bytes(), "organizer key").is_err());
+        assert!(encrypt(spoiler_log, "").is_err());
+        Ok(())
+    }
+}
@@ -14,7 +14,7 @@ use super::{
     },
 };
 
-fn compare_key_for_spoiler_log(field_number: FieldNumber) -> u8 {
+pub fn compare_key_for_spoiler_log(field_number: FieldNumber) -> u8 {
     if matches!(field_number, FieldNumber::TwinLabyrinthsRight) {
         FieldNumber::TwinLabyrinthsLeft.to_logic_number().unwrap() * 10 + 1
     } else {
@@ -165,6 +165,17 @@ pub struct SpoilerLog {
     maps: Vec<Checkpoint>,
 }
 
+impl SpoilerLog {
+    pub fn progression(&self) -> &[Sphere] {
+        &self.progression
+    }
+
+    /// Checkpoints outside the progression.
+    pub fn maps(&self) -> &[Checkpoint] {
+        &self.maps
+    }
+}
+
 impl fmt::Display for SpoilerLog {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for (i, sphere) in self.progression.iter().enumerate() {
@@ -250,6 +261,7 @@ impl SpoilerLogRef<'_> {
         }
     }
 
+    #[cfg(test)]
     pub fn count_checkpoints(&self) -> usize {
         self.progression
             .iter()
@@ -0,0 +1,272 @@
+use serde::Serialize;
+
+use crate::{dataset::spot::SpotName, script::enums::FieldNumber};
+
+use super::{
+    spoiler_log::{compare_key_for_spoiler_log, Checkpoint, SpoilerLog},
+    storage::item::Item,
+};
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpotView {
+    /// `mainWeapon`, `subWeapon`, `chest`, `seal`, `rom` or `talk`.
+    pub kind: &'static str,
+    pub name: String,
+    #[serde(skip)]
+    pub field_number: FieldNumber,
+    pub field: String,
+    pub item: String,
+    /// `None` outside the progression.
+    pub sphere: Option<usize>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShopSlotView {
+    pub item: String,
+    pub sphere: Option<usize>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShopView {
+    pub name: String,
+    #[serde(skip)]
+    pub field_number: FieldNumber,
+    pub field: String,
+    /// Slots that are not in the list they belong to are `None`.
+    pub slots: [Option<ShopSlotView>; 3],
+}
+
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointsView {
+    pub spots: Vec<SpotView>,
+    pub shops: Vec<ShopView>,
+    pub events: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldView {
+    #[serde(skip)]
+    pub field_number: FieldNumber,
+    pub field: String,
+    pub spots: Vec<SpotView>,
+    pub shops: Vec<ShopView>,
+}
+
+/// The spoiler log as data for the UI.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpoilerView {
+    pub spheres: Vec<CheckpointsView>,
+    /// The `[Maps]` section, outside the progression.
+    pub maps: CheckpointsView,
+    /// Every spot and shop grouped by field, in the order of the spoiler log.
+    pub fields: Vec<FieldView>,
+}
+
+fn field_name(field_number: FieldNumber) -> String {
+    format!("{:?}", field_number)
+}
+
+fn spot_view(
+    kind: &'static str,
+    field_number: FieldNumber,
+    name: &SpotName,
+    item: &Item,
+    sphere: Option<usize>,
+) -> SpotView {
+    SpotView {
+        kind,
+        name: name.get().to_owned(),
+        field_number,
+        field: field_name(field_number),
+        item: item.name.get().to_owned(),
+        sphere,
+    }
+}
+
+fn add_checkpoints<'a>(
+    view: &mut CheckpointsView,
+    checkpoints: impl IntoIterator<Item = &'a Checkpoint>,
+    sphere: Option<usize>,
+) {
+    for checkpoint in checkpoints {
+        let spot = match checkpoint {
+            Checkpoint::MainWeapon(x) => spot_view(
+                "mainWeapon",
+                x.spot.field_number(),
+                x.spot.name(),
+                &x.item,
+                sphere,
+            ),
+            Checkpoint::SubWeapon(x) => spot_view(
+                "subWeapon",
+                x.spot.field_number(),
+                x.spot.name(),
+                &x.item,
+                sphere,
+            ),
+            Checkpoint::Chest(x) => spot_view(
+                "chest",
+                x.spot.field_number(),
+                x.spot.name(),
+                &x.item,
+                sphere,
+            ),
+            Checkpoint::Seal(x) => spot_view(
+                "seal",
+                x.spot.field_number(),
+                x.spot.name(),
+                &x.item,
+                sphere,
+            ),
+            Checkpoint::Rom(x) => {
+                spot_view("rom", x.spot.field_number(), x.spot.name(), &x.item, sphere)
+            }
+            Checkpoint::Talk(x) => spot_view(
+                "talk",
+                x.spot.field_number(),
+                x.spot.name(),
+                &x.item,
+                sphere,
+            ),
+            Checkpoint::Shop(x) => {
+                let name = x.spot.name().get();
+                let index = match view.shops.iter().position(|shop| shop.name == name) {
+                    Some(index) => index,
+                    None => {
+                        view.shops.push(ShopView {
+                            name: name.to_owned(),
+                            field_number: x.spot.field_number(),
+                            field: field_name(x.spot.field_number()),
+                            slots: Default::default(),
+                        });
+                        view.shops.len() - 1
+                    }
+                };
+                view.shops[index].slots[x.idx] = Some(ShopSlotView {
+                    item: x.item.name.get().to_owned(),
+                    sphere,
+                });
+                continue;
+            }
+            Checkpoint::Event(flag) => {
+                view.events.push(flag.get().to_owned());
+                continue;
+            }
+        };
+        view.spots.push(spot);
+    }
+}
+
+fn by_field(all: CheckpointsView) -> Vec<FieldView> {
+    let mut fields: Vec<FieldView> = Vec::new();
+    let mut index_of = |field_number: FieldNumber| {
+        if let Some(index) = fields.iter().position(|x| x.field_number == field_number) {
+            return index;
+        }
+        fields.push(FieldView {
+            field_number,
+            field: field_name(field_number),
+            spots: Vec::new(),
+            shops: Vec::new(),
+        });
+        fields.len() - 1
+    };
+    let spots: Vec<_> = all
+        .spots
+        .into_iter()
+        .map(|x| (index_of(x.field_number), x))
+        .collect();
+    let shops: Vec<_> = all
+        .shops
+        .into_iter()
+        .map(|x| (index_of(x.field_number), x))
+        .collect();
+    for (index, spot) in spots {
+        fields[index].spots.push(spot);
+    }
+    for (index, shop) in shops {
+        fields[index].shops.push(shop);
+    }
+    fields.sort_by_key(|x| compare_key_for_spoiler_log(x.field_number));
+    fields
+}
+
+impl SpoilerView {
+    pub fn new(spoiler_log: &SpoilerLog) -> Self {
+        let mut spheres = Vec::new();
+        // Every checkpoint once, so that the slots of a shop are merged over the spheres.
+        let mut all = CheckpointsView::default();
+        for (i, sphere) in spoiler_log.progression().iter().enumerate() {
+            let mut view = CheckpointsView::default();
+            add_checkpoints(&mut view, &sphere.0, Some(i));
+            add_checkpoints(&mut all, &sphere.0, Some(i));
+            spheres.push(view);
+        }
+        let mut maps = CheckpointsView::default();
+        add_checkpoints(&mut maps, spoiler_log.maps(), None);
+        add_checkpoints(&mut all, spoiler_log.maps(), None);
+        Self {
+            spheres,
+            maps,
+            fields: by_field(all),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::{
+        app::read_game_structure_files_debug,
+        dataset::game_structure::GameStructure,
+        randomizer::{
+            randomize_items::seeded_spoiler, storage::create_source::create_source,
+            RandomizeOptions,
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_spoiler_view() -> Result<()> {
+        let game_structure_files = read_game_structure_files_debug().await?;
+        let game_structure = GameStructure::new(game_structure_files)?;
+        let opts = RandomizeOptions {
+            seed: "test".to_owned(),
+            ..Default::default()
+        };
+        let source = create_source(&game_structure, &opts)?;
+        let spoiler_log = seeded_spoiler(&source, &opts).0.to_owned();
+        let view = SpoilerView::new(&spoiler_log);
+
+        assert_eq!(view.spheres.len(), spoiler_log.progression().len());
+        let slots =
+            |shops: &[ShopView]| -> usize { shops.iter().flat_map(|x| &x.slots).flatten().count() };
+        let count = |x: &CheckpointsView| x.spots.len() + slots(&x.shops) + x.events.len();
+        let checkpoint_count = spoiler_log
+            .progression()
+            .iter()
+            .map(|x| x.0.len())
+            .sum::<usize>()
+            + spoiler_log.maps().len();
+        let view_count = view.spheres.iter().map(count).sum::<usize>() + count(&view.maps);
+        assert_eq!(view_count, checkpoint_count);
+
+        let field_spots: usize = view.fields.iter().map(|x| x.spots.len()).sum();
+        let field_slots: usize = view.fields.iter().map(|x| slots(&x.shops)).sum();
+        let events: usize = view.spheres.iter().map(|x| x.events.len()).sum();
+        assert_eq!(field_spots + field_slots + events, checkpoint_count);
+        assert!(view
+            .fields
+            .iter()
+            .all(|x| x.spots.iter().all(|y| y.field == x.field)));
+        Ok(())
+    }
+}
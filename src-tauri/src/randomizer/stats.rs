@@ -0,0 +1,272 @@
+use std::{collections::BTreeMap, fmt::Write, ops::Range, time::Instant};
+
+use anyhow::Result;
+use num_traits::FromPrimitive;
+use serde::Serialize;
+
+use crate::{dataset::game_structure::GameStructure, script::enums::FieldNumber};
+
+use super::{
+    randomize_items::seeded_spoiler,
+    spoiler_log::{CheckpointRef, SpoilerLogRef},
+    storage::{create_source::create_source, item::Item},
+    RandomizeOptions,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedStats {
+    pub seed: String,
+    pub spheres: usize,
+    /// Shuffles tried until one could be completed.
+    pub tries: usize,
+    /// The seeds are generated one at a time, each with every thread, as `randomize` does.
+    pub millis: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemStats {
+    pub name: String,
+    /// Occurrences in the progression over all seeds.
+    pub count: usize,
+    pub average_sphere: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldStats {
+    pub field: String,
+    /// Progression spots of the field per seed.
+    pub average_spots: f64,
+    pub average_sphere: f64,
+    /// The sphere in which the field yields its first item, averaged over the seeds.
+    pub average_first_sphere: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    pub settings: String,
+    pub average_spheres: f64,
+    pub average_tries: f64,
+    pub average_millis: f64,
+    pub seeds: Vec<SeedStats>,
+    pub items: Vec<ItemStats>,
+    pub fields: Vec<FieldStats>,
+}
+
+struct Record {
+    seed: SeedStats,
+    /// Item name, field and sphere of each checkpoint in the progression.
+    checkpoints: Vec<(String, FieldNumber, usize)>,
+}
+
+fn field_and_item<'a>(checkpoint: &CheckpointRef<'a>) -> Option<(FieldNumber, &'a Item)> {
+    Some(match checkpoint {
+        CheckpointRef::MainWeapon(x) => (x.spot.field_number(), x.item),
+        CheckpointRef::SubWeapon(x) => (x.spot.field_number(), x.item),
+        CheckpointRef::Chest(x) => (x.spot.field_number(), x.item),
+        CheckpointRef::Seal(x) => (x.spot.field_number(), x.item),
+        CheckpointRef::Rom(x) => (x.spot.field_number(), x.item),
+        CheckpointRef::Talk(x) => (x.spot.field_number(), x.item),
+        CheckpointRef::Shop(x) => (x.spot.field_number(), x.item),
+        CheckpointRef::Event(_) => return None,
+    })
+}
+
+fn record(seed: String, spoiler_log: &SpoilerLogRef, tries: usize, start: Instant) -> Record {
+    let checkpoints = spoiler_log
+        .progression
+        .iter()
+        .enumerate()
+        .flat_map(|(i, sphere)| sphere.0.iter().map(move |x| (i, x)))
+        .filter_map(|(i, checkpoint)| {
+            let (field, item) = field_and_item(checkpoint)?;
+            Some((item.name.get().to_owned(), field, i))
+        })
+        .collect();
+    Record {
+        seed: SeedStats {
+            seed,
+            spheres: spoiler_log.progression.len(),
+            tries,
+            millis: start.elapsed().as_secs_f64() * 1000.0,
+        },
+        checkpoints,
+    }
+}
+
+fn average(sum: usize, count: usize) -> f64 {
+    if count == 0 {
+        return 0.0;
+    }
+    sum as f64 / count as f64
+}
+
+fn aggregate(settings: String, records: Vec<Record>) -> Stats {
+    let seed_count = records.len();
+    let mut items: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    // spots, sum of spheres, sum of first spheres, seeds with the field
+    let mut fields: BTreeMap<u8, (usize, usize, usize, usize)> = BTreeMap::new();
+    for record in &records {
+        let mut first_spheres = BTreeMap::new();
+        for (name, field, sphere) in &record.checkpoints {
+            let item = items.entry(name).or_default();
+            item.0 += 1;
+            item.1 += sphere;
+            let field_stats = fields.entry(*field as u8).or_default();
+            field_stats.0 += 1;
+            field_stats.1 += sphere;
+            let first = first_spheres.entry(*field as u8).or_insert(*sphere);
+            *first = (*first).min(*sphere);
+        }
+        for (field, sphere) in first_spheres {
+            let field_stats = fields.get_mut(&field).unwrap();
+            field_stats.2 += sphere;
+            field_stats.3 += 1;
+        }
+    }
+    let sum = |f: fn(&SeedStats) -> f64| records.iter().map(|x| f(&x.seed)).sum::<f64>();
+    let per_seed = |total: f64| {
+        if seed_count == 0 {
+            0.0
+        } else {
+            total / seed_count as f64
+        }
+    };
+    Stats {
+        settings,
+        average_spheres: per_seed(sum(|x| x.spheres as f64)),
+        average_tries: per_seed(sum(|x| x.tries as f64)),
+        average_millis: per_seed(sum(|x| x.millis)),
+        items: items
+            .into_iter()
+            .map(|(name, (count, spheres))| ItemStats {
+                name: name.to_owned(),
+                count,
+                average_sphere: average(spheres, count),
+            })
+            .collect(),
+        fields: fields
+            .into_iter()
+            .map(
+                |(field, (spots, spheres, first_spheres, seeds))| FieldStats {
+                    field: FieldNumber::from_u8(field)
+                        .map_or_else(|| field.to_string(), |x| format!("{:?}", x)),
+                    average_spots: average(spots, seed_count),
+                    average_sphere: average(spheres, spots),
+                    average_first_sphere: average(first_spheres, seeds),
+                },
+            )
+            .collect(),
+        seeds: records.into_iter().map(|x| x.seed).collect(),
+    }
+}
+
+/// Generates the seeds of the range with the settings of `options` and aggregates their spoiler logs.
+/// Nothing is written. The seeds are the same as the ones `randomize` makes from the same options.
+// Only lmocodec runs the batch analysis.
+#[allow(dead_code)]
+pub fn seed_stats(
+    game_structure: &GameStructure,
+    options: &RandomizeOptions,
+    seeds: Range<u64>,
+) -> Result<Stats> {
+    let source = create_source(game_structure, options)?;
+    let records = seeds
+        .map(|seed| {
+            let start = Instant::now();
+            let options = RandomizeOptions {
+                seed: seed.to_string(),
+                ..*options
+            };
+            let (spoiler_log, tries) = seeded_spoiler(&source, &options);
+            record(options.seed, &spoiler_log, tries, start)
+        })
+        .collect();
+    Ok(aggregate(options.settings_code(), records))
+}
+
+fn csv_field(text: &str) -> String {
+    if !text.contains([',', '"', '\n']) {
+        return text.to_owned();
+    }
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// One table per kind of row: seeds, items and fields.
+// Only lmocodec runs the batch analysis.
+#[allow(dead_code)]
+pub fn to_csv(stats: &Stats) -> [(&'static str, String); 3] {
+    let mut seeds = "seed,spheres,tries,millis\n".to_owned();
+    for x in &stats.seeds {
+        let seed = csv_field(&x.seed);
+        writeln!(seeds, "{},{},{},{:.1}", seed, x.spheres, x.tries, x.millis).unwrap();
+    }
+    let mut items = "name,count,averageSphere\n".to_owned();
+    for x in &stats.items {
+        let name = csv_field(&x.name);
+        writeln!(items, "{},{},{:.3}", name, x.count, x.average_sphere).unwrap();
+    }
+    let mut fields = "field,averageSpots,averageSphere,averageFirstSphere\n".to_owned();
+    for x in &stats.fields {
+        writeln!(
+            fields,
+            "{},{:.3},{:.3},{:.3}",
+            x.field, x.average_spots, x.average_sphere, x.average_first_sphere
+        )
+        .unwrap();
+    }
+    [("seeds", seeds), ("items", items), ("fields", fields)]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::read_game_structure_files_debug;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_seed_stats() -> Result<()> {
+        let game_structure_files = read_game_structure_files_debug().await?;
+        let game_structure = GameStructure::new(game_structure_files)?;
+        let opts = RandomizeOptions {
+            seed: String::new(),
+            shuffle_secret_roms: true,
+            need_glitches: false,
+            absolutely_shuffle: false,
+        };
+        let stats = seed_stats(&game_structure, &opts, 0..3)?;
+        assert_eq!(stats.settings, "R--");
+        assert_eq!(
+            stats
+                .seeds
+                .iter()
+                .map(|x| x.seed.as_str())
+                .collect::<Vec<_>>(),
+            ["0", "1", "2"]
+        );
+
+        let source = create_source(&game_structure, &opts)?;
+        let opts = RandomizeOptions {
+            seed: "1".to_owned(),
+            ..opts
+        };
+        let (spoiler_log, tries) = seeded_spoiler(&source, &opts);
+        assert_eq!(stats.seeds[1].spheres, spoiler_log.progression.len());
+        assert_eq!(stats.seeds[1].tries, tries);
+
+        let spot_count: usize = stats.items.iter().map(|x| x.count).sum();
+        let average_spots: f64 = stats.fields.iter().map(|x| x.average_spots).sum();
+        assert!((average_spots * 3.0 - spot_count as f64).abs() < 1e-6);
+        assert!(stats
+            .fields
+            .iter()
+            .all(|x| x.average_first_sphere <= x.average_sphere));
+
+        let [(_, seeds), _, _] = to_csv(&stats);
+        assert_eq!(seeds.lines().count(), 4);
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ mod assertions;
 pub mod create_source;
 pub mod item;
 
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, fmt};
 
 use anyhow::Result;
 
@@ -156,3 +156,37 @@ impl Storage {
             .chain(self.talks.iter().map(|x| &x.item))
     }
 }
+
+impl fmt::Display for Storage {
+    /// One line per spot, in the same notation as the spoiler log.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for x in self.main_weapons.values() {
+            writeln!(f, "{} = {}", x.spot, x.item.name.get())?;
+        }
+        for x in self.sub_weapons.values() {
+            writeln!(f, "{} = {}", x.spot, x.item.name.get())?;
+        }
+        for x in self.chests.values() {
+            writeln!(f, "{} = {}", x.spot, x.item.name.get())?;
+        }
+        for x in self.seals.values() {
+            writeln!(f, "{} = {}", x.spot, x.item.name.get())?;
+        }
+        for x in self.roms.values() {
+            writeln!(f, "{} = {}", x.spot, x.item.name.get())?;
+        }
+        for x in &self.talks {
+            writeln!(f, "{} = {}", x.spot, x.item.name.get())?;
+        }
+        for shop in self.shops.chunk_by(|a, b| a.spot.items() == b.spot.items()) {
+            let names: Vec<_> = (0..3)
+                .map(|idx| {
+                    let item = shop.iter().find(|x| x.idx == idx);
+                    item.map_or("_", |x| x.item.name.get())
+                })
+                .collect();
+            writeln!(f, "{} = {}", shop[0].spot, names.join(", "))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{bail, Result};
+
+use crate::dataset::spot::AnyOfAllRequirements;
+
+use super::{
+    spoiler::{sphere::is_reachable, GLITCH},
+    storage::{item::StrategyFlag, Storage},
+    RandomizeOptions,
+};
+
+/// Placements keyed by spot, one entry per shop slot, from text in the spoiler log notation.
+fn assignments(text: &str) -> BTreeMap<String, String> {
+    let mut map = BTreeMap::new();
+    for line in text.lines() {
+        let Some((spot, item)) = line.split_once(" = ") else {
+            continue;
+        };
+        if !spot.contains("_Shop(") {
+            map.insert(spot.to_owned(), item.to_owned());
+            continue;
+        }
+        for (idx, item) in item.split(", ").enumerate() {
+            if item != "_" {
+                map.insert(format!("{} #{}", spot, idx), item.to_owned());
+            }
+        }
+    }
+    map
+}
+
+fn verify_placements(storage: &Storage, spoiler_log: &str) -> Result<()> {
+    let expected = assignments(spoiler_log);
+    let actual = assignments(&storage.to_string());
+    let mismatches: Vec<_> = expected
+        .keys()
+        .chain(actual.keys())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .filter(|spot| expected.get(*spot) != actual.get(*spot))
+        .map(|spot| {
+            let expected = expected.get(spot).map_or("-", |x| x.as_str());
+            let actual = actual.get(spot).map_or("-", |x| x.as_str());
+            format!("{}: expected {}, found {}", spot, expected, actual)
+        })
+        .collect();
+    if !mismatches.is_empty() {
+        bail!(
+            "placements disagree with the spoiler log:\n{}",
+            mismatches.join("\n")
+        );
+    }
+    Ok(())
+}
+
+struct Check<'a> {
+    spot: String,
+    required: bool,
+    requirements: Option<&'a AnyOfAllRequirements>,
+    name: &'a StrategyFlag,
+}
+
+fn checks(storage: &Storage) -> Vec<Check<'_>> {
+    let check = |spot: &dyn ToString, requirements, name| Check {
+        spot: spot.to_string(),
+        required: true,
+        requirements,
+        name,
+    };
+    let mut checks = Vec::new();
+    for x in storage.main_weapons.values() {
+        checks.push(check(&x.spot, x.spot.requirements(), &x.item.name));
+    }
+    for x in storage.sub_weapons.values() {
+        checks.push(check(&x.spot, x.spot.requirements(), &x.item.name));
+    }
+    for x in storage.chests.values() {
+        checks.push(check(&x.spot, x.spot.requirements(), &x.item.name));
+    }
+    for x in storage.seals.values() {
+        checks.push(check(&x.spot, x.spot.requirements(), &x.item.name));
+    }
+    for x in storage.roms.values() {
+        checks.push(check(&x.spot, Some(x.spot.requirements()), &x.item.name));
+    }
+    for x in &storage.talks {
+        checks.push(check(&x.spot, x.spot.requirements(), &x.item.name));
+    }
+    for x in &storage.shops {
+        let spot = format!("{} #{}", x.spot, x.idx);
+        checks.push(Check {
+            // Consumables are not required to be reachable, the same as in the randomizer.
+            required: !x.item.name.is_consumable(),
+            ..check(&spot, x.spot.requirements(), &x.item.name)
+        });
+    }
+    // Some events are only for glitches.
+    for x in &storage.events {
+        checks.push(Check {
+            required: false,
+            ..check(&x.name.get(), Some(&x.requirements), &x.name)
+        });
+    }
+    checks
+}
+
+fn verify_beatable(storage: &Storage, options: &RandomizeOptions) -> Result<()> {
+    let mut strategy_flags: HashSet<&str> = HashSet::new();
+    let mut sacred_orb_count = 0;
+    if options.need_glitches {
+        strategy_flags.insert(GLITCH.get());
+    }
+    let mut remaining = checks(storage);
+    loop {
+        let (reachables, unreachables): (Vec<_>, Vec<_>) = remaining
+            .into_iter()
+            .partition(|x| is_reachable(x.requirements, &strategy_flags, sacred_orb_count));
+        remaining = unreachables;
+        if reachables.is_empty() {
+            break;
+        }
+        for check in reachables {
+            if strategy_flags.insert(check.name.get()) && check.name.is_sacred_orb() {
+                sacred_orb_count += 1;
+            }
+        }
+    }
+    let spots: Vec<_> = remaining
+        .into_iter()
+        .filter(|x| x.required)
+        .map(|x| x.spot)
+        .collect();
+    if !spots.is_empty() {
+        bail!("unreachable:\n{}", spots.join("\n"));
+    }
+    Ok(())
+}
+
+/// Checks placements read back from the output against the spoiler log,
+/// then collects every item with the logic alone to make sure that the game can be completed.
+pub fn verify(storage: &Storage, options: &RandomizeOptions, spoiler_log: &str) -> Result<()> {
+    verify_placements(storage, spoiler_log)?;
+    verify_beatable(storage, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assignments() {
+        let text = "\
+SEED 0123ABCD R--
+
+[Sphere 0]
+Surface_Shop(handScanner, ammunition, buckler) = _, shuriken, _
+Surface_Chest(holyGrail) = feather
+event:reachedGateOfGuidance
+
+[Sphere 1]
+Surface_Shop(handScanner, ammunition, buckler) = msx2, _, pistolAmmo
+";
+        let map = assignments(text);
+        let shop = "Surface_Shop(handScanner, ammunition, buckler)";
+        assert_eq!(map.len(), 4);
+        assert_eq!(map[&format!("{} #0", shop)], "msx2");
+        assert_eq!(map[&format!("{} #1", shop)], "shuriken");
+        assert_eq!(map[&format!("{} #2", shop)], "pistolAmmo");
+        assert_eq!(map["Surface_Chest(holyGrail)"], "feather");
+    }
+}
@@ -1,5 +1,13 @@
 mod consts;
 pub mod data;
+pub mod decompiler;
+// Only lmocodec compares scripts.
+#[allow(dead_code)]
+pub mod diff;
 pub mod editor;
 pub mod enums;
 pub mod file;
+// Only lmocodec exports the screen graph.
+#[allow(dead_code)]
+pub mod screen_graph;
+pub mod validation;
@@ -1,5 +1,12 @@
-pub const BLANK_TALK_NUMBER: i32 = 772;
+use std::ops::RangeInclusive;
+
+pub const BLANK_TALK_NUMBER: u16 = 772;
+/// Talks of the vanilla script.dat. Other versions may have more, but not fewer.
+pub const VANILLA_TALK_COUNT: usize = 905;
 
 pub const ALWAYS_ON_FLAG_NO: u16 = 40;
 pub const UNUSED_PR3_FLAG_NO: u16 = 114;
-pub const MEMO_FLAG_BASE_NO: u16 = 7500;
+
+/// Flags for new objects are allocated from these ranges. See `FlagAllocator`.
+pub const SAVE_FLAG_RANGE: RangeInclusive<u16> = 6000..=6999;
+pub const ONE_TIME_FLAG_RANGE: RangeInclusive<u16> = 7400..=7999;
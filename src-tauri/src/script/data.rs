@@ -1,5 +1,7 @@
+pub mod flags;
 pub mod item;
 pub mod object;
+pub mod patch_rules;
 pub mod script;
 pub mod shop_items_data;
 pub mod talk;
@@ -0,0 +1,225 @@
+mod index;
+
+use std::{collections::BTreeSet, ops::RangeInclusive};
+
+use anyhow::{bail, Result};
+
+use super::script::Script;
+
+pub use index::{FlagIndex, ObjectLocation};
+
+/// Every flag number referenced by the script.
+///
+/// Besides the flags of the index, any object operand or flag of talk command 3 that fits in
+/// a flag number is counted too, since they may hold flags as well. This over-approximates
+/// the usage, which is the safe side for allocation.
+pub struct FlagUsage(BTreeSet<u16>);
+
+impl FlagUsage {
+    pub fn new(script: &Script) -> Result<Self> {
+        let mut flags: BTreeSet<_> = FlagIndex::new(script)?.flags().collect();
+        let talk_flags = script.talks.iter().flat_map(index::talk_flags);
+        flags.extend(talk_flags.map(|(_, flag)| flag));
+        for field in script.worlds.iter().flat_map(|x| &x.fields) {
+            let field_ops = field
+                .objects
+                .iter()
+                .flat_map(|x| [x.op1, x.op2, x.op3, x.op4]);
+            let map_ops = field
+                .maps
+                .iter()
+                .flat_map(|x| &x.objects)
+                .flat_map(|x| [x.op1(), x.op2(), x.op3(), x.op4()]);
+            flags.extend(
+                field_ops
+                    .chain(map_ops)
+                    .filter_map(|x| u16::try_from(x).ok()),
+            );
+        }
+        Ok(Self(flags))
+    }
+
+    pub fn contains(&self, flag: u16) -> bool {
+        self.0.contains(&flag)
+    }
+}
+
+/// Hands out flags that are not referenced anywhere in the script.
+pub struct FlagAllocator {
+    used: FlagUsage,
+}
+
+impl FlagAllocator {
+    pub fn new(script: &Script) -> Result<Self> {
+        Ok(Self {
+            used: FlagUsage::new(script)?,
+        })
+    }
+
+    pub fn allocate(&mut self, range: RangeInclusive<u16>) -> Result<u16> {
+        let Some(flag) = range.clone().find(|&x| !self.used.contains(x)) else {
+            bail!("no unused flag in {}..={}", range.start(), range.end())
+        };
+        self.used.0.insert(flag);
+        Ok(flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::script::{
+        data::{
+            object::{Object, Start, UnknownObject},
+            script::{Field, Map, World},
+            talk::{write_u16, Talk},
+        },
+        file::dat::code_map,
+    };
+
+    use super::{
+        index::{FlagAccess, FlagLocation},
+        *,
+    };
+
+    fn talk(data: &[u8]) -> Talk {
+        let code_map = code_map();
+        Talk::from_text(
+            &data
+                .iter()
+                .map(|&x| code_map[x as usize])
+                .collect::<String>(),
+        )
+    }
+
+    fn unknown(number: u16, op1: i32, op3: i32, op4: i32, starts: &[u32]) -> UnknownObject {
+        UnknownObject {
+            number,
+            x: 0,
+            y: 0,
+            op1,
+            op2: -1,
+            op3,
+            op4,
+            starts: starts
+                .iter()
+                .map(|&flag| Start {
+                    flag,
+                    run_when: true,
+                })
+                .collect(),
+        }
+    }
+
+    fn script() -> Script {
+        let (hi, lo) = write_u16(7402);
+        let map = Map {
+            attrs: (0, 0, 0),
+            up: (0, 0, 0, 0),
+            right: (0, 0, 0, 0),
+            down: (0, 0, 0, 0),
+            left: (0, 0, 0, 0),
+            objects: [
+                unknown(20, 7400, -1, -1, &[99999]),
+                unknown(59, 0, 7403, -1, &[7405]),
+                unknown(70, 0, -1, 1_012_340, &[]),
+            ]
+            .into_iter()
+            .map(|x| Object::new(x.number, x.x, x.y, x.op1, x.op2, x.op3, x.op4, x.starts))
+            .collect::<Result<_>>()
+            .unwrap(),
+        };
+        Script {
+            talks: vec![talk(&[b'A', 2, hi, lo, b'B'])],
+            worlds: vec![World {
+                number: 0,
+                fields: vec![Field {
+                    attrs: (1, 0, 0, 0, 0),
+                    chip_line: (0, 0),
+                    hits: vec![],
+                    animes: vec![],
+                    objects: vec![unknown(140, 7401, -1, -1, &[6000])],
+                    maps: vec![map],
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_flag_usage() -> Result<()> {
+        let usage = FlagUsage::new(&script())?;
+        for flag in [234, 6000, 7400, 7401, 7402, 7403, 7405] {
+            assert!(usage.contains(flag), "{}", flag);
+        }
+        assert!(!usage.contains(7404));
+        Ok(())
+    }
+
+    #[test]
+    fn test_allocations_do_not_collide() -> Result<()> {
+        let script = script();
+        let usage = FlagUsage::new(&script)?;
+        let mut allocator = FlagAllocator::new(&script)?;
+        let allocated: Vec<_> = (0..5)
+            .map(|_| allocator.allocate(7400..=7409))
+            .collect::<Result<_>>()?;
+        assert_eq!(allocated, [7404, 7406, 7407, 7408, 7409]);
+        assert!(allocated.iter().all(|&x| !usage.contains(x)));
+        assert_eq!(
+            allocated.iter().collect::<HashSet<_>>().len(),
+            allocated.len()
+        );
+        assert!(allocator.allocate(7400..=7409).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_flag_index() -> Result<()> {
+        let index = FlagIndex::new(&script())?;
+
+        let refs = index.get(7405);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].access, FlagAccess::Start { run_when: true });
+        assert!(refs[0].access.is_reader());
+        let FlagLocation::Object(location) = &refs[0].location else {
+            panic!("{}", refs[0]);
+        };
+        assert_eq!(
+            (location.map, location.index, location.number),
+            (Some((0, 0, 0)), 1, 59)
+        );
+
+        let refs = index.get(7402);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].access, FlagAccess::TalkCommand);
+        assert_eq!(refs[0].location, FlagLocation::Talk(0));
+
+        let refs = index.get(7401);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(
+            refs[0].to_string(),
+            "trigger_flag world 0 field 1 (Surface) #0 object 140 at (0, 0)"
+        );
+        assert!(refs[0].access.is_reader());
+        let FlagLocation::Object(location) = &refs[0].location else {
+            panic!("{}", refs[0]);
+        };
+        assert_eq!((location.map, location.number), (None, 140));
+
+        let refs = index.get(234);
+        assert_eq!(refs.len(), 1);
+        assert!(!refs[0].access.is_reader());
+
+        assert!(index.get(7404).is_empty());
+        // Operands that are not known to hold flags are only counted for the allocation.
+        assert!(index.get(0).is_empty());
+        assert!(FlagUsage::new(&script())?.contains(0));
+
+        assert_eq!(
+            index.entry(7403).to_string(),
+            "7403:\n  read  check_flag world 0 field 1 (Surface) map 0,0,0 #1 object 59 at (0, 0)\n"
+        );
+        Ok(())
+    }
+}
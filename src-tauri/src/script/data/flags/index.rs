@@ -0,0 +1,266 @@
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::Result;
+use num_traits::FromPrimitive;
+
+use crate::script::{
+    data::{
+        object::{ItemShop, Object, UnknownObject},
+        script::Script,
+        talk::{read_u16, Talk},
+    },
+    enums::FieldNumber,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlagAccess {
+    /// `<START>` condition of an object.
+    Start { run_when: bool },
+    /// Flag field of a typed object, named after its accessor.
+    Field { name: &'static str, writes: bool },
+    /// Flag set when the item of the object is taken. See `Object::set_flag`.
+    ItemSetFlag,
+    /// Talk command 2 sets the flag.
+    TalkCommand,
+    /// Flag set when the item in the slot of a shop is bought.
+    ShopItem(u8),
+}
+
+impl FlagAccess {
+    pub fn is_reader(self) -> bool {
+        matches!(self, Self::Start { .. } | Self::Field { writes: false, .. })
+    }
+}
+
+impl fmt::Display for FlagAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Start { run_when } => write!(f, "start({})", *run_when as u8),
+            Self::Field { name, .. } => write!(f, "{}", name),
+            Self::ItemSetFlag => write!(f, "item"),
+            Self::TalkCommand => write!(f, "cmd2"),
+            Self::ShopItem(slot) => write!(f, "shop slot {}", slot),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ObjectLocation {
+    pub world: u8,
+    pub field: u8,
+    /// `None` for objects placed directly under `<FIELD>`.
+    pub map: Option<(u8, u8, u8)>,
+    pub index: usize,
+    pub number: u16,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl fmt::Display for ObjectLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "world {} field {}", self.world, self.field)?;
+        if let Some(field) = FieldNumber::from_u8(self.field) {
+            write!(f, " ({:?})", field)?;
+        }
+        if let Some((a, b, c)) = self.map {
+            write!(f, " map {},{},{}", a, b, c)?;
+        }
+        write!(
+            f,
+            " #{} object {} at ({}, {})",
+            self.index, self.number, self.x, self.y
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FlagLocation {
+    Object(ObjectLocation),
+    Talk(usize),
+}
+
+impl fmt::Display for FlagLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Object(location) => location.fmt(f),
+            Self::Talk(number) => write!(f, "talk {}", number),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FlagReference {
+    pub access: FlagAccess,
+    pub location: FlagLocation,
+}
+
+impl fmt::Display for FlagReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.access, self.location)
+    }
+}
+
+/// Cross-reference from each flag to the objects and talks that read or write it.
+///
+/// Only the fields known to hold flags are listed. The raw operands of the other objects and
+/// talk command 3 are left out, see `FlagUsage` for the allocation.
+pub struct FlagIndex(BTreeMap<u16, Vec<FlagReference>>);
+
+impl FlagIndex {
+    pub fn new(script: &Script) -> Result<Self> {
+        let mut zelf = Self(BTreeMap::new());
+        for (number, talk) in script.talks.iter().enumerate() {
+            for (_, flag) in talk_flags(talk).into_iter().filter(|&(cmd, _)| cmd == 2) {
+                zelf.insert(flag, FlagAccess::TalkCommand, FlagLocation::Talk(number));
+            }
+        }
+        for world in &script.worlds {
+            for field in &world.fields {
+                let location = |map, index, number, x, y| ObjectLocation {
+                    world: world.number,
+                    field: field.attrs.0,
+                    map,
+                    index,
+                    number,
+                    x,
+                    y,
+                };
+                for (index, obj) in field.objects.iter().enumerate() {
+                    let location = location(None, index, obj.number, obj.x, obj.y);
+                    for (access, flag) in unknown_object_flags(obj) {
+                        zelf.insert(flag, access, FlagLocation::Object(location.clone()));
+                    }
+                }
+                for map in &field.maps {
+                    for (index, obj) in map.objects.iter().enumerate() {
+                        let location =
+                            location(Some(map.attrs), index, obj.number(), obj.x(), obj.y());
+                        for (access, flag) in object_flags(obj) {
+                            zelf.insert(flag, access, FlagLocation::Object(location.clone()));
+                        }
+                    }
+                }
+            }
+        }
+        for shop in script.shops() {
+            let Some(shop) = ItemShop::try_from_shop_object(shop, &script.talks)? else {
+                continue;
+            };
+            let location = FlagLocation::Talk(shop.item_data_talk_number() as usize);
+            let items = shop.items();
+            for (slot, item) in [&items.0, &items.1, &items.2].into_iter().enumerate() {
+                let access = FlagAccess::ShopItem(slot as u8);
+                zelf.insert(item.flag(), access, location.clone());
+            }
+        }
+        Ok(zelf)
+    }
+
+    fn insert(&mut self, flag: u16, access: FlagAccess, location: FlagLocation) {
+        let reference = FlagReference { access, location };
+        self.0.entry(flag).or_default().push(reference);
+    }
+
+    pub fn get(&self, flag: u16) -> &[FlagReference] {
+        self.0.get(&flag).map(|x| &x[..]).unwrap_or_default()
+    }
+
+    pub fn flags(&self) -> impl Iterator<Item = u16> + '_ {
+        self.0.keys().copied()
+    }
+
+    pub fn entry(&self, flag: u16) -> FlagEntry<'_> {
+        FlagEntry { index: self, flag }
+    }
+}
+
+/// Every flag of the index, as `FlagEntry` prints them.
+impl fmt::Display for FlagIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.flags().try_for_each(|flag| self.entry(flag).fmt(f))
+    }
+}
+
+/// A flag with its readers first, then its writers.
+pub struct FlagEntry<'a> {
+    index: &'a FlagIndex,
+    flag: u16,
+}
+
+impl fmt::Display for FlagEntry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}:", self.flag)?;
+        let references = self.index.get(self.flag);
+        for reference in references.iter().filter(|x| x.access.is_reader()) {
+            writeln!(f, "  read  {}", reference)?;
+        }
+        for reference in references.iter().filter(|x| !x.access.is_reader()) {
+            writeln!(f, "  write {}", reference)?;
+        }
+        Ok(())
+    }
+}
+
+/// Flags of talk commands 2 and 3, with the command number.
+pub(super) fn talk_flags(talk: &Talk) -> Vec<(u8, u16)> {
+    let data = talk.as_bytes();
+    talk.control_talk_command_ranges()
+        .into_iter()
+        .filter(|range| range.end <= data.len())
+        .map(|range| &data[range])
+        .filter(|cmd| (cmd[0] == 2 || cmd[0] == 3) && cmd[1] >= 1)
+        .map(|cmd| (cmd[0], read_u16(cmd[1], cmd[2])))
+        .collect()
+}
+
+fn object_flags(obj: &Object) -> Vec<(FlagAccess, u16)> {
+    let field = |name, writes, flag| (FlagAccess::Field { name, writes }, flag);
+    let starts = obj.starts().iter().filter_map(|x| {
+        let access = FlagAccess::Start {
+            run_when: x.run_when,
+        };
+        Some((access, u16::try_from(x.flag).ok()?))
+    });
+    let fields = match obj {
+        Object::Chest(x) => vec![field("open_flag", false, x.open_flag())],
+        Object::Shutter(x) => vec![field("open_flag", false, x.open_flag())],
+        Object::Trigger(x) => vec![field("set_flag", true, x.set_flag())],
+        Object::Tablet(x) => x
+            .set_flag()
+            .map(|flag| field("set_flag", true, flag))
+            .into_iter()
+            .collect(),
+        Object::MapRewrite(x) => vec![field("check_flag", false, x.check_flag())],
+        // Set when the wall breaks.
+        Object::BreakableWall(x) => vec![field("check_flag", true, x.check_flag())],
+        Object::Trap(x) => vec![field("trigger_flag", false, x.trigger_flag())],
+        Object::SubWeapon(_)
+        | Object::Shop(_)
+        | Object::Rom(_)
+        | Object::Seal(_)
+        | Object::MainWeapon(_)
+        | Object::ItemGiver(_)
+        | Object::Unknown(_) => vec![],
+    };
+    let set_flag = obj.set_flag().ok();
+    starts
+        .chain(fields)
+        .chain(set_flag.map(|flag| (FlagAccess::ItemSetFlag, flag)))
+        .collect()
+}
+
+/// Objects under `<FIELD>` are read untyped, so they are typed here the way the maps are.
+/// One that does not parse keeps only its starts.
+fn unknown_object_flags(obj: &UnknownObject) -> Vec<(FlagAccess, u16)> {
+    let typed = Object::new(
+        obj.number,
+        obj.x,
+        obj.y,
+        obj.op1,
+        obj.op2,
+        obj.op3,
+        obj.op4,
+        obj.starts.clone(),
+    );
+    object_flags(&typed.unwrap_or_else(|_| Object::Unknown(obj.clone())))
+}
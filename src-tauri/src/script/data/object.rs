@@ -1,4 +1,5 @@
 mod field_objects;
+mod mechanism_objects;
 mod shop_object;
 pub mod starts;
 mod weapon_objects;
@@ -14,6 +15,10 @@ use super::{
 };
 
 pub use field_objects::{ChestObject, RomObject, SealObject, UnknownObject};
+pub use mechanism_objects::{
+    BreakableWallObject, ItemGiverObject, MapRewriteObject, ShutterObject, TabletObject,
+    TrapObject, TriggerObject,
+};
 pub use shop_object::{ItemShop, Shop, ShopObject};
 pub use weapon_objects::{MainWeaponObject, SubWeaponObject};
 
@@ -138,6 +143,61 @@ fn create_main_weapon_object(
     Ok(MainWeaponObject::new(x, y, main_weapon, starts))
 }
 
+/// The mechanisms decode only when their operands have the expected shape,
+/// since unlike the items, nothing forces that on them. Other ones stay `Unknown`.
+fn create_mechanism_object(
+    number: u16,
+    x: i32,
+    y: i32,
+    [op1, op2, op3, op4]: [i32; 4],
+    starts: Vec<Start>,
+) -> Option<Object> {
+    Some(match number {
+        7 => {
+            let content = u16::try_from(op1).ok()?;
+            let amount = u16::try_from(op2).ok()?;
+            let giver = ItemGiverObject::new(x, y, content, amount, [op3, op4], starts);
+            Object::ItemGiver(giver)
+        }
+        20 => {
+            let open_flag = u16::try_from(op1).ok()?;
+            Object::Shutter(ShutterObject::new(x, y, open_flag, [op2, op3, op4], starts))
+        }
+        22 => {
+            let set_flag = u16::try_from(op3).ok()?;
+            Object::Trigger(TriggerObject::new(x, y, set_flag, [op1, op2, op4], starts))
+        }
+        37 => {
+            let talk_number = u16::try_from(op1).ok()?;
+            let set_flag = match op2 {
+                -1 => None,
+                _ => Some(u16::try_from(op2).ok()?),
+            };
+            let tablet = TabletObject::new(x, y, talk_number, set_flag, [op3, op4], starts);
+            Object::Tablet(tablet)
+        }
+        59 => {
+            let check_flag = u16::try_from(op3).ok()?;
+            Object::MapRewrite(MapRewriteObject::new(
+                x,
+                y,
+                check_flag,
+                [op1, op2, op4],
+                starts,
+            ))
+        }
+        70 => {
+            let wall = BreakableWallObject::new(x, y, [op1, op2, op3], op4, starts).ok()?;
+            Object::BreakableWall(wall)
+        }
+        140 => {
+            let trigger_flag = u16::try_from(op1).ok()?;
+            Object::Trap(TrapObject::new(x, y, trigger_flag, [op2, op3, op4], starts))
+        }
+        _ => return None,
+    })
+}
+
 #[derive(Clone)]
 pub enum Object {
     Chest(ChestObject),
@@ -146,6 +206,13 @@ pub enum Object {
     Rom(RomObject),
     Seal(SealObject),
     MainWeapon(MainWeaponObject),
+    ItemGiver(ItemGiverObject),
+    Shutter(ShutterObject),
+    Trigger(TriggerObject),
+    Tablet(TabletObject),
+    MapRewrite(MapRewriteObject),
+    BreakableWall(BreakableWallObject),
+    Trap(TrapObject),
     Unknown(UnknownObject),
 }
 
@@ -168,16 +235,22 @@ impl Object {
             32 => Object::Rom(create_rom_object(x, y, op1, op2, op3, op4, starts)?),
             71 => Object::Seal(create_seal_object(x, y, op1, op2, op3, op4, starts)?),
             77 => Object::MainWeapon(create_main_weapon_object(x, y, op1, op2, op3, op4, starts)?),
-            _ => Object::Unknown(UnknownObject {
-                number,
-                x,
-                y,
-                op1,
-                op2,
-                op3,
-                op4,
-                starts,
-            }),
+            _ => {
+                let ops = [op1, op2, op3, op4];
+                if let Some(obj) = create_mechanism_object(number, x, y, ops, starts.clone()) {
+                    return Ok(obj);
+                }
+                Object::Unknown(UnknownObject {
+                    number,
+                    x,
+                    y,
+                    op1,
+                    op2,
+                    op3,
+                    op4,
+                    starts,
+                })
+            }
         })
     }
 
@@ -189,6 +262,13 @@ impl Object {
             Self::Rom(_) => 32,
             Self::Seal(_) => 71,
             Self::MainWeapon(_) => 77,
+            Self::ItemGiver(_) => 7,
+            Self::Shutter(_) => 20,
+            Self::Trigger(_) => 22,
+            Self::Tablet(_) => 37,
+            Self::MapRewrite(_) => 59,
+            Self::BreakableWall(_) => 70,
+            Self::Trap(_) => 140,
             Self::Unknown(obj) => obj.number,
         }
     }
@@ -200,6 +280,13 @@ impl Object {
             Self::Rom(obj) => obj.x(),
             Self::Seal(obj) => obj.x(),
             Self::MainWeapon(obj) => obj.x(),
+            Self::ItemGiver(obj) => obj.x(),
+            Self::Shutter(obj) => obj.x(),
+            Self::Trigger(obj) => obj.x(),
+            Self::Tablet(obj) => obj.x(),
+            Self::MapRewrite(obj) => obj.x(),
+            Self::BreakableWall(obj) => obj.x(),
+            Self::Trap(obj) => obj.x(),
             Self::Unknown(obj) => obj.x,
         }
     }
@@ -211,6 +298,13 @@ impl Object {
             Self::Rom(obj) => obj.y(),
             Self::Seal(obj) => obj.y(),
             Self::MainWeapon(obj) => obj.y(),
+            Self::ItemGiver(obj) => obj.y(),
+            Self::Shutter(obj) => obj.y(),
+            Self::Trigger(obj) => obj.y(),
+            Self::Tablet(obj) => obj.y(),
+            Self::MapRewrite(obj) => obj.y(),
+            Self::BreakableWall(obj) => obj.y(),
+            Self::Trap(obj) => obj.y(),
             Self::Unknown(obj) => obj.y,
         }
     }
@@ -222,6 +316,13 @@ impl Object {
             Self::Rom(obj) => obj.rom().content as i32,
             Self::Seal(obj) => obj.seal().content as i32,
             Self::MainWeapon(obj) => obj.main_weapon().content as i32,
+            Self::ItemGiver(obj) => obj.ops()[0],
+            Self::Shutter(obj) => obj.ops()[0],
+            Self::Trigger(obj) => obj.ops()[0],
+            Self::Tablet(obj) => obj.ops()[0],
+            Self::MapRewrite(obj) => obj.ops()[0],
+            Self::BreakableWall(obj) => obj.ops()[0],
+            Self::Trap(obj) => obj.ops()[0],
             Self::Unknown(obj) => obj.op1,
         }
     }
@@ -237,6 +338,13 @@ impl Object {
             Self::Rom(obj) => obj.rom().flag as i32,
             Self::Seal(obj) => obj.seal().flag as i32,
             Self::MainWeapon(obj) => obj.main_weapon().flag as i32,
+            Self::ItemGiver(obj) => obj.ops()[1],
+            Self::Shutter(obj) => obj.ops()[1],
+            Self::Trigger(obj) => obj.ops()[1],
+            Self::Tablet(obj) => obj.ops()[1],
+            Self::MapRewrite(obj) => obj.ops()[1],
+            Self::BreakableWall(obj) => obj.ops()[1],
+            Self::Trap(obj) => obj.ops()[1],
             Self::Unknown(obj) => obj.op2,
         }
     }
@@ -248,6 +356,13 @@ impl Object {
             Self::Rom(_) => -1,
             Self::Seal(_) => -1,
             Self::MainWeapon(_) => -1,
+            Self::ItemGiver(obj) => obj.ops()[2],
+            Self::Shutter(obj) => obj.ops()[2],
+            Self::Trigger(obj) => obj.ops()[2],
+            Self::Tablet(obj) => obj.ops()[2],
+            Self::MapRewrite(obj) => obj.ops()[2],
+            Self::BreakableWall(obj) => obj.ops()[2],
+            Self::Trap(obj) => obj.ops()[2],
             Self::Unknown(obj) => obj.op3,
         }
     }
@@ -259,6 +374,13 @@ impl Object {
             Self::Rom(_) => -1,
             Self::Seal(_) => -1,
             Self::MainWeapon(_) => -1,
+            Self::ItemGiver(obj) => obj.ops()[3],
+            Self::Shutter(obj) => obj.ops()[3],
+            Self::Trigger(obj) => obj.ops()[3],
+            Self::Tablet(obj) => obj.ops()[3],
+            Self::MapRewrite(obj) => obj.ops()[3],
+            Self::BreakableWall(obj) => obj.ops()[3],
+            Self::Trap(obj) => obj.ops()[3],
             Self::Unknown(obj) => obj.op4,
         }
     }
@@ -270,6 +392,13 @@ impl Object {
             Self::Rom(obj) => obj.starts(),
             Self::Seal(obj) => obj.starts(),
             Self::MainWeapon(obj) => obj.starts(),
+            Self::ItemGiver(obj) => obj.starts(),
+            Self::Shutter(obj) => obj.starts(),
+            Self::Trigger(obj) => obj.starts(),
+            Self::Tablet(obj) => obj.starts(),
+            Self::MapRewrite(obj) => obj.starts(),
+            Self::BreakableWall(obj) => obj.starts(),
+            Self::Trap(obj) => obj.starts(),
             Self::Unknown(obj) => &obj.starts,
         }
     }
@@ -282,7 +411,62 @@ impl Object {
             Self::Rom(obj) => obj.rom().flag,
             Self::Seal(obj) => obj.seal().flag,
             Self::MainWeapon(obj) => obj.main_weapon().flag,
-            Self::Unknown(UnknownObject { number, .. }) => bail!("invalid number: {}", number),
+            Self::ItemGiver(_)
+            | Self::Shutter(_)
+            | Self::Trigger(_)
+            | Self::Tablet(_)
+            | Self::MapRewrite(_)
+            | Self::BreakableWall(_)
+            | Self::Trap(_)
+            | Self::Unknown(_) => bail!("invalid number: {}", self.number()),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ops(obj: &Object) -> [i32; 4] {
+        [obj.op1(), obj.op2(), obj.op3(), obj.op4()]
+    }
+
+    #[test]
+    fn test_mechanism_objects() -> Result<()> {
+        let cases = [
+            (7, [7, 999, -1, -1]),
+            (20, [800, 1, 2, 3]),
+            (22, [2, 2, 7000, -1]),
+            (37, [772, -1, -1, -1]),
+            (37, [772, 900, -1, -1]),
+            (59, [1, 2, 743, -1]),
+            (70, [1, 2, 3, 12345]),
+            (140, [700, 1, 2, 3]),
+        ];
+        for (number, [op1, op2, op3, op4]) in cases {
+            let obj = Object::new(number, 1, 2, op1, op2, op3, op4, vec![])?;
+            assert!(!matches!(obj, Object::Unknown(_)), "{}", number);
+            assert_eq!(obj.number(), number);
+            assert_eq!(ops(&obj), [op1, op2, op3, op4]);
+        }
+
+        let Object::BreakableWall(wall) = Object::new(70, 0, 0, 1, 2, 3, 12345, vec![])? else {
+            unreachable!()
+        };
+        assert_eq!(wall.check_flag(), 234);
+        let Object::Trap(mut trap) = Object::new(140, 0, 0, 700, 1, 2, 3, vec![])? else {
+            unreachable!()
+        };
+        trap.set_trigger_flag(701);
+        assert_eq!(ops(&Object::Trap(trap)), [701, 1, 2, 3]);
+
+        // Unexpected shapes are kept as they are.
+        let obj = Object::new(20, 1, 2, -1, 1, 2, 3, vec![])?;
+        assert!(matches!(obj, Object::Unknown(_)));
+        assert_eq!(ops(&obj), [-1, 1, 2, 3]);
+        let obj = Object::new(70, 1, 2, 1, 2, 3, -1, vec![])?;
+        assert!(matches!(obj, Object::Unknown(_)));
+        assert!(BreakableWallObject::new(1, 2, [1, 2, 3], -1, vec![]).is_err());
+        Ok(())
+    }
+}
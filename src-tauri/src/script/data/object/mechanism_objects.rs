@@ -0,0 +1,317 @@
+use anyhow::{bail, Result};
+
+use super::Start;
+
+/// Object 7. Gives `amount` of `content`, such as 6 for weights and 7 for coins.
+#[derive(Clone)]
+pub struct ItemGiverObject {
+    x: i32,
+    y: i32,
+    content: u16,
+    amount: u16,
+    /// op3 and op4.
+    ops: [i32; 2],
+    starts: Vec<Start>,
+}
+
+impl ItemGiverObject {
+    pub fn new(
+        x: i32,
+        y: i32,
+        content: u16,
+        amount: u16,
+        ops: [i32; 2],
+        starts: Vec<Start>,
+    ) -> Self {
+        Self {
+            x,
+            y,
+            content,
+            amount,
+            ops,
+            starts,
+        }
+    }
+
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+    pub fn content(&self) -> u16 {
+        self.content
+    }
+    pub fn amount(&self) -> u16 {
+        self.amount
+    }
+    pub fn ops(&self) -> [i32; 4] {
+        let [op3, op4] = self.ops;
+        [self.content as i32, self.amount as i32, op3, op4]
+    }
+    pub fn starts(&self) -> &[Start] {
+        &self.starts
+    }
+}
+
+/// Object 20. Opens when `open_flag` is set; items behind it are made to appear with it.
+#[derive(Clone)]
+pub struct ShutterObject {
+    x: i32,
+    y: i32,
+    open_flag: u16,
+    /// op2 to op4.
+    ops: [i32; 3],
+    starts: Vec<Start>,
+}
+
+impl ShutterObject {
+    pub fn new(x: i32, y: i32, open_flag: u16, ops: [i32; 3], starts: Vec<Start>) -> Self {
+        Self {
+            x,
+            y,
+            open_flag,
+            ops,
+            starts,
+        }
+    }
+
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+    pub fn open_flag(&self) -> u16 {
+        self.open_flag
+    }
+    pub fn ops(&self) -> [i32; 4] {
+        let [op2, op3, op4] = self.ops;
+        [self.open_flag as i32, op2, op3, op4]
+    }
+    pub fn starts(&self) -> &[Start] {
+        &self.starts
+    }
+}
+
+/// Object 22. Sets `set_flag` when Lemeza enters its area.
+#[derive(Clone)]
+pub struct TriggerObject {
+    x: i32,
+    y: i32,
+    set_flag: u16,
+    /// op1, op2 and op4.
+    ops: [i32; 3],
+    starts: Vec<Start>,
+}
+
+impl TriggerObject {
+    pub fn new(x: i32, y: i32, set_flag: u16, ops: [i32; 3], starts: Vec<Start>) -> Self {
+        Self {
+            x,
+            y,
+            set_flag,
+            ops,
+            starts,
+        }
+    }
+
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+    pub fn set_flag(&self) -> u16 {
+        self.set_flag
+    }
+    pub fn ops(&self) -> [i32; 4] {
+        let [op1, op2, op4] = self.ops;
+        [op1, op2, self.set_flag as i32, op4]
+    }
+    pub fn starts(&self) -> &[Start] {
+        &self.starts
+    }
+}
+
+/// Object 37. Shows a talk when read with the Hand Scanner, and sets `set_flag` if any.
+#[derive(Clone)]
+pub struct TabletObject {
+    x: i32,
+    y: i32,
+    talk_number: u16,
+    set_flag: Option<u16>,
+    /// op3 and op4.
+    ops: [i32; 2],
+    starts: Vec<Start>,
+}
+
+impl TabletObject {
+    pub fn new(
+        x: i32,
+        y: i32,
+        talk_number: u16,
+        set_flag: Option<u16>,
+        ops: [i32; 2],
+        starts: Vec<Start>,
+    ) -> Self {
+        Self {
+            x,
+            y,
+            talk_number,
+            set_flag,
+            ops,
+            starts,
+        }
+    }
+
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+    pub fn talk_number(&self) -> u16 {
+        self.talk_number
+    }
+    pub fn set_flag(&self) -> Option<u16> {
+        self.set_flag
+    }
+    pub fn ops(&self) -> [i32; 4] {
+        let set_flag = self.set_flag.map_or(-1, |x| x as i32);
+        let [op3, op4] = self.ops;
+        [self.talk_number as i32, set_flag, op3, op4]
+    }
+    pub fn starts(&self) -> &[Start] {
+        &self.starts
+    }
+}
+
+/// Object 59. Rewrites the map, such as opening a wall, depending on `check_flag`.
+#[derive(Clone)]
+pub struct MapRewriteObject {
+    x: i32,
+    y: i32,
+    check_flag: u16,
+    /// op1, op2 and op4.
+    ops: [i32; 3],
+    starts: Vec<Start>,
+}
+
+impl MapRewriteObject {
+    pub fn new(x: i32, y: i32, check_flag: u16, ops: [i32; 3], starts: Vec<Start>) -> Self {
+        Self {
+            x,
+            y,
+            check_flag,
+            ops,
+            starts,
+        }
+    }
+
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+    pub fn check_flag(&self) -> u16 {
+        self.check_flag
+    }
+    pub fn set_check_flag(&mut self, flag: u16) {
+        self.check_flag = flag;
+    }
+    pub fn ops(&self) -> [i32; 4] {
+        let [op1, op2, op4] = self.ops;
+        [op1, op2, self.check_flag as i32, op4]
+    }
+    pub fn starts(&self) -> &[Start] {
+        &self.starts
+    }
+}
+
+/// Object 70. The flag set when the wall breaks is packed into the digits of op4.
+#[derive(Clone)]
+pub struct BreakableWallObject {
+    x: i32,
+    y: i32,
+    /// op1 to op3.
+    ops: [i32; 3],
+    /// op4, never negative.
+    packed: i32,
+    starts: Vec<Start>,
+}
+
+impl BreakableWallObject {
+    pub fn new(x: i32, y: i32, ops: [i32; 3], packed: i32, starts: Vec<Start>) -> Result<Self> {
+        if packed < 0 {
+            bail!("invalid parameter: op4={}", packed);
+        }
+        Ok(Self {
+            x,
+            y,
+            ops,
+            packed,
+            starts,
+        })
+    }
+
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+    pub fn check_flag(&self) -> u16 {
+        ((self.packed % 10000) / 10) as u16
+    }
+    pub fn ops(&self) -> [i32; 4] {
+        [self.ops[0], self.ops[1], self.ops[2], self.packed]
+    }
+    pub fn starts(&self) -> &[Start] {
+        &self.starts
+    }
+}
+
+/// Object 140. Springs when `trigger_flag` is set.
+#[derive(Clone)]
+pub struct TrapObject {
+    x: i32,
+    y: i32,
+    trigger_flag: u16,
+    /// op2 to op4.
+    ops: [i32; 3],
+    starts: Vec<Start>,
+}
+
+impl TrapObject {
+    pub fn new(x: i32, y: i32, trigger_flag: u16, ops: [i32; 3], starts: Vec<Start>) -> Self {
+        Self {
+            x,
+            y,
+            trigger_flag,
+            ops,
+            starts,
+        }
+    }
+
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+    pub fn trigger_flag(&self) -> u16 {
+        self.trigger_flag
+    }
+    pub fn set_trigger_flag(&mut self, flag: u16) {
+        self.trigger_flag = flag;
+    }
+    pub fn ops(&self) -> [i32; 4] {
+        let [op2, op3, op4] = self.ops;
+        [self.trigger_flag as i32, op2, op3, op4]
+    }
+    pub fn starts(&self) -> &[Start] {
+        &self.starts
+    }
+}
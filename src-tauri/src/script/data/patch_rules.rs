@@ -0,0 +1,286 @@
+use std::{str::FromStr, sync::LazyLock};
+
+use anyhow::{anyhow, bail, Result};
+use num_traits::FromPrimitive;
+use serde::Deserialize;
+
+use crate::script::enums::{self, to_pascal_case, FieldNumber};
+
+use super::{object::Object, script::Script};
+
+const SCRIPT_PATCHES_YAML: &str = include_str!("../../../res/script_patches.yml");
+const VERSION: u32 = 1;
+
+/// The error is kept as text so that every caller can report it.
+static RULES: LazyLock<Result<Vec<Rule>, String>> =
+    LazyLock::new(|| parse(SCRIPT_PATCHES_YAML).map_err(|e| format!("{:#}", e)));
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ItemRef {
+    Talk(String),
+    SubWeapon(String),
+}
+
+impl ItemRef {
+    pub fn talk_item(name: &str) -> Result<enums::TalkItem> {
+        let equipment = enums::Equipment::from_str(&to_pascal_case(name))
+            .map_err(|_| anyhow!("unknown equipment: {}", name))?;
+        Ok(enums::TalkItem::Equipment(equipment))
+    }
+
+    pub fn sub_weapon(name: &str) -> Result<enums::SubWeapon> {
+        enums::SubWeapon::from_str(&to_pascal_case(name))
+            .map_err(|_| anyhow!("unknown sub weapon: {}", name))
+    }
+
+    fn check(&self) -> Result<()> {
+        match self {
+            Self::Talk(name) => Self::talk_item(name).map(|_| ()),
+            Self::SubWeapon(name) => Self::sub_weapon(name).map(|_| ()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FlagRef {
+    VanillaItemFlag(ItemRef),
+    PlacedItemFlag(ItemRef),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Number(i32),
+    Flag(FlagRef),
+}
+
+impl Value {
+    fn check(&self) -> Result<()> {
+        match self {
+            Self::Number(_) => Ok(()),
+            Self::Flag(FlagRef::VanillaItemFlag(x) | FlagRef::PlacedItemFlag(x)) => x.check(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Matcher {
+    field: Option<String>,
+    map: Option<(u8, u8, u8)>,
+    number: u16,
+    x: Option<i32>,
+    y: Option<i32>,
+    ops: Option<[i32; 4]>,
+    start_flags: Option<Vec<u32>>,
+}
+
+impl Matcher {
+    fn matches(&self, field_number: FieldNumber, map: (u8, u8, u8), obj: &Object) -> bool {
+        let ops = [obj.op1(), obj.op2(), obj.op3(), obj.op4()];
+        let start_flags = || obj.starts().iter().map(|x| x.flag).collect::<Vec<_>>();
+        self.number == obj.number()
+            && self
+                .field
+                .as_ref()
+                .is_none_or(|x| *x == format!("{:?}", field_number))
+            && self.map.is_none_or(|x| x == map)
+            && self.x.is_none_or(|x| x == obj.x())
+            && self.y.is_none_or(|y| y == obj.y())
+            && self.ops.is_none_or(|x| x == ops)
+            && self
+                .start_flags
+                .as_ref()
+                .is_none_or(|x| *x == start_flags())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FlagReplacement {
+    pub from: Value,
+    pub to: Value,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Set {
+    pub op1: Option<Value>,
+    pub op2: Option<Value>,
+    pub op3: Option<Value>,
+    pub op4: Option<Value>,
+    /// The starts are left as they are unless this is given.
+    #[serde(default)]
+    pub start_flags: Vec<FlagReplacement>,
+}
+
+impl Set {
+    pub fn is_empty(&self) -> bool {
+        [&self.op1, &self.op2, &self.op3, &self.op4]
+            .iter()
+            .all(|x| x.is_none())
+            && self.start_flags.is_empty()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Rule {
+    pub name: String,
+    #[serde(rename = "match")]
+    matcher: Matcher,
+    #[serde(default)]
+    not_a_spot: bool,
+    #[serde(default)]
+    pub set: Set,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScriptPatches {
+    version: u32,
+    rules: Vec<Rule>,
+}
+
+fn parse(yaml: &str) -> Result<Vec<Rule>> {
+    let patches: ScriptPatches = serde_yaml::from_str(yaml)?;
+    if patches.version != VERSION {
+        bail!("unsupported script patches version: {}", patches.version);
+    }
+    for rule in &patches.rules {
+        let set = &rule.set;
+        let values = [&set.op1, &set.op2, &set.op3, &set.op4]
+            .into_iter()
+            .flatten()
+            .chain(set.start_flags.iter().flat_map(|x| [&x.from, &x.to]));
+        for value in values {
+            value
+                .check()
+                .map_err(|e| e.context(format!("rule {}", rule.name)))?;
+        }
+        if let Some(field) = &rule.matcher.field {
+            if !(0..)
+                .map_while(FieldNumber::from_u8)
+                .any(|x| format!("{:?}", x) == *field)
+            {
+                bail!("rule {}: unknown field: {}", rule.name, field);
+            }
+        }
+    }
+    Ok(patches.rules)
+}
+
+/// The rules of res/script_patches.yml.
+pub fn rules() -> Result<&'static [Rule]> {
+    RULES
+        .as_deref()
+        .map_err(|e| anyhow!("invalid script patches: {}", e))
+}
+
+/// The first rule that matches the object.
+pub fn find<'a>(
+    rules: &'a [Rule],
+    field_number: FieldNumber,
+    map: (u8, u8, u8),
+    obj: &Object,
+) -> Option<&'a Rule> {
+    rules
+        .iter()
+        .find(|x| x.matcher.matches(field_number, map, obj))
+}
+
+/// Whether a rule excludes the object from the item spots.
+pub fn is_not_a_spot(
+    rules: &[Rule],
+    field_number: FieldNumber,
+    map: (u8, u8, u8),
+    obj: &Object,
+) -> bool {
+    find(rules, field_number, map, obj).is_some_and(|x| x.not_a_spot)
+}
+
+/// How many objects of the script each rule matches.
+fn match_counts<'a>(rules: &'a [Rule], script: &Script) -> Vec<(&'a Rule, usize)> {
+    rules
+        .iter()
+        .map(|rule| {
+            let count = script
+                .worlds
+                .iter()
+                .flat_map(|x| &x.fields)
+                .filter_map(|field| Some((FieldNumber::from_u8(field.attrs.0)?, field)))
+                .flat_map(|(field_number, field)| {
+                    field.maps.iter().flat_map(move |map| {
+                        map.objects
+                            .iter()
+                            .filter(move |obj| rule.matcher.matches(field_number, map.attrs, obj))
+                    })
+                })
+                .count();
+            (rule, count)
+        })
+        .collect()
+}
+
+/// Rules that match no object in the script. The editors assume every rule is in use.
+pub fn unmatched_rules(script: &Script) -> Result<Vec<String>> {
+    Ok(match_counts(rules()?, script)
+        .into_iter()
+        .filter(|(_, count)| *count == 0)
+        .map(|(rule, _)| rule.name.clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::read_script_dat_debug;
+    use crate::script::file::scriptconverter::read_script_dat;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        assert_eq!(rules()?.len(), 4);
+        assert!(parse(&SCRIPT_PATCHES_YAML.replace("version: 1", "version: 2")).is_err());
+        assert!(parse(&SCRIPT_PATCHES_YAML.replace("talk: pepper", "talk: peper")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "needs the vanilla script.dat in LMO_SCRIPT_DAT"]
+    fn test_rules_match_vanilla_script() -> Result<()> {
+        let script = read_script_dat(&read_script_dat_debug()?)?;
+        let rules = rules()?;
+        for (rule, count) in match_counts(rules, &script) {
+            // A rule pinned to a position or to start flags is about one object.
+            let matcher = &rule.matcher;
+            let pinned =
+                matcher.x.is_some() && matcher.y.is_some() || matcher.start_flags.is_some();
+            assert!(count > 0, "{}", rule.name);
+            assert!(
+                !pinned || count == 1,
+                "{} matches {} objects",
+                rule.name,
+                count
+            );
+        }
+        // No rule is hidden behind an earlier one.
+        for field in script.worlds.iter().flat_map(|x| &x.fields) {
+            let Some(field_number) = FieldNumber::from_u8(field.attrs.0) else {
+                continue;
+            };
+            for map in &field.maps {
+                for obj in &map.objects {
+                    let matched = rules
+                        .iter()
+                        .filter(|x| x.matcher.matches(field_number, map.attrs, obj))
+                        .count();
+                    assert!(matched <= 1, "{} rules match an object", matched);
+                }
+            }
+        }
+        Ok(())
+    }
+}
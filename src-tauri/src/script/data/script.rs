@@ -12,6 +12,7 @@ use super::{
         ChestObject, MainWeaponObject, Object, RomObject, SealObject, ShopObject, SubWeaponObject,
         UnknownObject,
     },
+    patch_rules::{is_not_a_spot, rules},
     talk::Talk,
 };
 
@@ -72,11 +73,10 @@ pub struct Script {
 }
 
 impl Script {
+    /// Fails on text that `stringify` would not give back, such as CRLF line breaks.
     pub fn parse(txt: &str) -> Result<Script> {
         let (talks, worlds) = parse_script_txt(txt)?;
-        let zelf = Self { talks, worlds };
-        debug_assert_eq!(txt, zelf.stringify());
-        Ok(zelf)
+        Ok(Self { talks, worlds })
     }
 
     pub fn stringify(&self) -> String {
@@ -108,17 +108,18 @@ impl Script {
         })
     }
 
-    pub fn chests(&self) -> impl Iterator<Item = &ChestObject> {
-        self.view_objects()
-            // without 2nd twinStatue
-            .filter(|x| {
-                !(x.number() == 1
-                    && x.x() == 8192
-                    && x.y() == 6144
-                    && x.op1() == 420
-                    && x.op2() == 14
-                    && x.op3() == 766
-                    && x.op4() == 0)
+    /// Fails if the script patch rules, which exclude some chests, are broken.
+    pub fn chests(&self) -> Result<impl Iterator<Item = &ChestObject>> {
+        let rules = rules()?;
+        Ok(self
+            .worlds
+            .iter()
+            .flat_map(|x| &x.fields)
+            .flat_map(|field| field.maps.iter().map(move |map| (field.number(), map)))
+            .flat_map(move |(field_number, map)| {
+                map.objects
+                    .iter()
+                    .filter(move |x| !is_not_a_spot(rules, field_number, map.attrs, x))
             })
             .filter_map(|x| {
                 let Object::Chest(x) = x else {
@@ -135,7 +136,7 @@ impl Script {
                             ..
                         })
                 )
-            })
+            }))
     }
 
     pub fn seals(&self) -> impl Iterator<Item = &SealObject> {
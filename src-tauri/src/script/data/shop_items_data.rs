@@ -10,7 +10,9 @@ use super::{
 
 pub fn parse(talk: &Talk) -> Result<(ShopItem, ShopItem, ShopItem)> {
     let data = talk.as_bytes();
-    debug_assert_eq!(data.len(), 7 * 3);
+    if data.len() != 7 * 3 {
+        bail!("invalid shop items data: {:?}", data);
+    }
     let mut iter = (0..3)
         .map(|i| i * 7)
         .map(|x| ShopItem::from_bytes(&data[x..x + 7]));
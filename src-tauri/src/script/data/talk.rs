@@ -1,3 +1,5 @@
+mod readable;
+
 use core::fmt;
 use std::ops::Range;
 
@@ -26,6 +28,10 @@ impl Talk {
         Self(text.chars().map(|c| char_to_code[&c]).collect())
     }
 
+    pub fn new(data: Vec<u8>) -> Self {
+        Self(data)
+    }
+
     pub fn from_bytes(data: Vec<u8>) -> Self {
         debug_assert_eq!(data.len(), 7 * 3);
         Self(data)
@@ -0,0 +1,205 @@
+use std::{fmt::Write, str::FromStr};
+
+use anyhow::{anyhow, bail, Result};
+use num_traits::FromPrimitive;
+
+use crate::script::{
+    enums::{self, to_camel_case, to_pascal_case},
+    file::dat::{code_map, reverse_code_map},
+};
+
+use super::{read_u16, write_u16, Talk};
+
+const NEW_LINE: u8 = 10;
+
+fn write_raw(text: &mut String, bytes: &[u8]) {
+    let hex: Vec<_> = bytes.iter().map(|x| format!("{:02x}", x)).collect();
+    write!(text, "{{raw:{}}}", hex.join(",")).unwrap();
+}
+
+fn write_command(text: &mut String, cmd: &[u8]) {
+    match cmd[0] {
+        1 | 7 | 8 => write!(text, "{{cmd{}}}", cmd[0]).unwrap(),
+        2 if cmd[1] >= 1 => write!(text, "{{flag:{}=1}}", read_u16(cmd[1], cmd[2])).unwrap(),
+        3 if cmd[1] >= 1 => write!(text, "{{cmd3:{}}}", read_u16(cmd[1], cmd[2])).unwrap(),
+        4 => match enums::Equipment::from_u8(cmd[1].wrapping_sub(1)) {
+            Some(x) if cmd[1] >= 1 => {
+                write!(text, "{{item:equipment:{}}}", to_camel_case(&x.to_string())).unwrap()
+            }
+            _ => write!(text, "{{cmd4:{}}}", cmd[1]).unwrap(),
+        },
+        5 => match enums::Rom::from_u8(cmd[1].wrapping_sub(1)) {
+            Some(x) if cmd[1] >= 1 => {
+                write!(text, "{{item:rom:{}}}", to_camel_case(&x.to_string())).unwrap()
+            }
+            _ => write!(text, "{{cmd5:{}}}", cmd[1]).unwrap(),
+        },
+        6 => write!(text, "{{cmd6:{}}}", cmd[1]).unwrap(),
+        _ => write_raw(text, cmd),
+    }
+}
+
+fn parse_u8(value: &str) -> Result<u8> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("invalid byte: {}", value))
+}
+
+fn parse_u16(value: &str) -> Result<(u8, u8)> {
+    let value: u16 = value
+        .parse()
+        .map_err(|_| anyhow!("invalid number: {}", value))?;
+    if value > read_u16(u8::MAX, u8::MAX) {
+        bail!("number out of range: {}", value);
+    }
+    Ok(write_u16(value))
+}
+
+fn parse_tag(tag: &str) -> Result<Vec<u8>> {
+    let (name, args) = tag.split_once(':').unwrap_or((tag, ""));
+    Ok(match (name, args) {
+        ("cmd1", "") => vec![1],
+        ("cmd7", "") => vec![7],
+        ("cmd8", "") => vec![8],
+        ("flag", args) => {
+            let Some(flag) = args.strip_suffix("=1") else {
+                bail!("flag must be set to 1: {{{}}}", tag)
+            };
+            let (hi, lo) = parse_u16(flag)?;
+            vec![2, hi, lo]
+        }
+        ("cmd3", args) => {
+            let (hi, lo) = parse_u16(args)?;
+            vec![3, hi, lo]
+        }
+        ("cmd4", args) => vec![4, parse_u8(args)?],
+        ("cmd5", args) => vec![5, parse_u8(args)?],
+        ("cmd6", args) => vec![6, parse_u8(args)?],
+        ("item", args) => match args.split_once(':') {
+            Some(("equipment", name)) if !name.is_empty() => {
+                let equipment = enums::Equipment::from_str(&to_pascal_case(name))?;
+                vec![4, equipment as u8 + 1]
+            }
+            Some(("rom", name)) if !name.is_empty() => {
+                let rom = enums::Rom::from_str(&to_pascal_case(name))?;
+                vec![5, rom as u8 + 1]
+            }
+            _ => bail!("invalid item: {{{}}}", tag),
+        },
+        ("raw", args) => args
+            .split(',')
+            .map(|x| u8::from_str_radix(x, 16).map_err(|_| anyhow!("invalid hex: {}", x)))
+            .collect::<Result<_>>()?,
+        _ => bail!("unknown tag: {{{}}}", tag),
+    })
+}
+
+impl Talk {
+    /// Text mapped through `code_map` with control commands written as `{...}` tags.
+    /// `{` and `}` are not in `code_map`, so tags never clash with the text.
+    pub fn to_readable(&self) -> String {
+        let code_map = code_map();
+        let data = &self.0;
+        let mut text = String::new();
+        let mut ranges = self.control_talk_command_ranges().into_iter().peekable();
+        let mut i = 0;
+        while i < data.len() {
+            let Some(range) = ranges.next_if(|x| x.start == i) else {
+                match data[i] {
+                    0..=15 => write_raw(&mut text, &data[i..i + 1]),
+                    x => text.push(code_map[x as usize]),
+                }
+                i += 1;
+                continue;
+            };
+            if data[i] == NEW_LINE {
+                text.push('\n');
+            } else if range.end > data.len() {
+                write_raw(&mut text, &data[i..]);
+            } else {
+                write_command(&mut text, &data[range.clone()]);
+            }
+            i = range.end;
+        }
+        text
+    }
+
+    pub fn from_readable(text: &str) -> Result<Self> {
+        let char_to_code = reverse_code_map();
+        let mut data = Vec::new();
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            if c == '{' {
+                let Some(end) = rest.find('}') else {
+                    bail!("unterminated tag: {}", rest)
+                };
+                data.append(&mut parse_tag(&rest[1..end])?);
+                rest = &rest[end + 1..];
+                continue;
+            }
+            let code = char_to_code
+                .get(&c)
+                .ok_or_else(|| anyhow!("invalid character: {:?}", c))?;
+            data.push(*code);
+            rest = &rest[c.len_utf8()..];
+        }
+        Ok(Self(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{app::read_script_dat_debug, script::file::scriptconverter::read_script_dat};
+
+    use super::*;
+
+    #[test]
+    fn test_readable_round_trip() -> Result<()> {
+        let (hi, lo) = write_u16(1234);
+        let cases: Vec<Vec<u8>> = vec![
+            vec![
+                0x30, 2, hi, lo, 4, 8, NEW_LINE, 5, 1, 1, 0x41, 7, 8, 6, 3, 3, hi, lo,
+            ],
+            vec![2, 0, 5, 4, 0, 4, 255, 5, 200, 0, 9, 11, 15],
+            // truncated commands
+            vec![0x41, 2, 3],
+            vec![4],
+            (0..=255).collect(),
+        ];
+        for data in cases {
+            let talk = Talk(data.clone());
+            let readable = talk.to_readable();
+            assert_eq!(Talk::from_readable(&readable)?.0, data, "{}", readable);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_readable_tags() -> Result<()> {
+        let talk = Talk::from_readable("A{flag:1234=1}{item:equipment:feather}\nB{cmd7}")?;
+        let (hi, lo) = write_u16(1234);
+        let feather = enums::Equipment::Feather as u8 + 1;
+        assert_eq!(talk.0, [0x41, 2, hi, lo, 4, feather, NEW_LINE, 0x42, 7]);
+        assert_eq!(
+            talk.to_readable(),
+            "A{flag:1234=1}{item:equipment:feather}\nB{cmd7}"
+        );
+        assert!(Talk::from_readable("{item:equipment:nothing}").is_err());
+        assert!(Talk::from_readable("{item:rom:ü}").is_err());
+        assert!(Talk::from_readable("{flag:1234").is_err());
+        assert!(Talk::from_readable("{").is_err());
+        Ok(())
+    }
+
+    /// Run once with the Japanese and once with the English script.dat.
+    #[test]
+    #[ignore = "needs the vanilla script.dat in LMO_SCRIPT_DAT"]
+    fn test_readable_round_trip_vanilla_script() -> Result<()> {
+        let script = read_script_dat(&read_script_dat_debug()?)?;
+        for (i, talk) in script.talks.iter().enumerate() {
+            let readable = talk.to_readable();
+            assert_eq!(Talk::from_readable(&readable)?.0, talk.0, "talk {}", i);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::{btree_map::Entry, BTreeMap};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::{
+    randomizer::storage::{self, Storage},
+    script::{
+        data::{
+            item::{ChestItem, Equipment, Item, Rom},
+            object::{ItemShop, Object, Shop},
+            script::{Map, Script},
+            shop_items_data::{self, ShopItem},
+        },
+        enums::{self, FieldNumber},
+    },
+};
+
+/// The item flag identifies an item, except for consumables that share the "no flag" value.
+/// The content and amount tell those apart.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum ItemKey {
+    MainWeapon(enums::MainWeapon, u16),
+    SubWeapon(enums::SubWeapon, u8, u16),
+    Equipment(enums::Equipment, u16),
+    Rom(enums::Rom, u16),
+    Seal(enums::Seal, u16),
+}
+
+impl ItemKey {
+    fn new(item: &Item) -> Self {
+        match item {
+            Item::MainWeapon(x) => Self::MainWeapon(x.content, x.flag),
+            Item::SubWeapon(x) => Self::SubWeapon(x.content, x.amount, x.flag),
+            Item::Equipment(x) => Self::Equipment(x.content, x.flag),
+            Item::Rom(x) => Self::Rom(x.content, x.flag),
+            Item::Seal(x) => Self::Seal(x.content, x.flag),
+        }
+    }
+
+    fn from_object(obj: &Object) -> Option<Self> {
+        Some(match obj {
+            Object::Chest(obj) => match obj.item() {
+                ChestItem::None(_) => return None,
+                ChestItem::Equipment(x) => Self::Equipment(x.content, x.flag),
+                ChestItem::Rom(x) => Self::Rom(x.content, x.flag),
+            },
+            Object::SubWeapon(obj) => Self::new(&Item::SubWeapon(obj.sub_weapon().clone())),
+            Object::MainWeapon(obj) => Self::new(&Item::MainWeapon(obj.main_weapon().clone())),
+            Object::Seal(obj) => Self::new(&Item::Seal(obj.seal().clone())),
+            Object::Rom(obj) => Self::new(&Item::Rom(obj.rom().clone())),
+            _ => return None,
+        })
+    }
+
+    fn from_shop_item(item: &ShopItem) -> Self {
+        match item {
+            ShopItem::SubWeapon(x) => Self::new(&Item::SubWeapon(x.item.clone())),
+            ShopItem::Equipment(x) => Self::new(&Item::Equipment(x.item.clone())),
+            ShopItem::Rom(x) => Self::new(&Item::Rom(x.item.clone())),
+        }
+    }
+
+    fn from_talk_item(talk_item: enums::TalkItem, flag: u16) -> Self {
+        match talk_item {
+            enums::TalkItem::Equipment(content) => Self::new(&Item::Equipment(Equipment {
+                content,
+                price: None,
+                flag,
+            })),
+            enums::TalkItem::Rom(content) => Self::new(&Item::Rom(Rom {
+                content,
+                price: None,
+                flag,
+            })),
+        }
+    }
+}
+
+/// The items of the source storage, looked up by what the script says about them.
+struct ItemPool(BTreeMap<ItemKey, Vec<storage::item::Item>>);
+
+impl ItemPool {
+    fn new(vanilla: &Script, source: &Storage) -> Result<Self> {
+        let mut map: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for item in source.all_items() {
+            let key = ItemKey::new(&Item::new(&item.src, vanilla)?);
+            map.entry(key).or_default().push(item.clone());
+        }
+        Ok(Self(map))
+    }
+
+    fn take(&mut self, key: ItemKey) -> Result<storage::item::Item> {
+        self.0
+            .get_mut(&key)
+            .and_then(|x| x.pop())
+            .ok_or_else(|| anyhow!("unknown item: {:?}", key))
+    }
+}
+
+/// The editors put the new item at the position of the old one.
+/// Items behind the memos of hand scanner spots are raised by half a square.
+fn find_placed_item(map: &Map, old_obj: &Object) -> Option<ItemKey> {
+    let find = |y| {
+        map.objects
+            .iter()
+            .filter(|obj| obj.x() == old_obj.x() && obj.y() == y)
+            .find_map(ItemKey::from_object)
+    };
+    let found = find(old_obj.y());
+    if found.is_none() && matches!(old_obj, Object::Rom(_)) {
+        return find(old_obj.y() - 2048);
+    }
+    found
+}
+
+#[derive(Default)]
+struct Placements {
+    main_weapons: BTreeMap<enums::MainWeapon, ItemKey>,
+    sub_weapons: BTreeMap<(FieldNumber, enums::SubWeapon), ItemKey>,
+    chests: BTreeMap<(FieldNumber, enums::ChestItem), ItemKey>,
+    seals: BTreeMap<enums::Seal, ItemKey>,
+    roms: BTreeMap<enums::Rom, ItemKey>,
+}
+
+fn insert_once<K: Ord>(
+    map: &mut BTreeMap<K, ItemKey>,
+    key: K,
+    found: impl FnOnce() -> Result<ItemKey>,
+) -> Result<()> {
+    // Surface objects are duplicated at night. The first one wins.
+    if let Entry::Vacant(entry) = map.entry(key) {
+        entry.insert(found()?);
+    }
+    Ok(())
+}
+
+impl Placements {
+    fn new(vanilla: &Script, target: &Script) -> Result<Self> {
+        let mut zelf = Self::default();
+        if vanilla.worlds.len() != target.worlds.len() {
+            bail!("world count mismatch");
+        }
+        for (vanilla_world, target_world) in vanilla.worlds.iter().zip(&target.worlds) {
+            if vanilla_world.fields.len() != target_world.fields.len() {
+                bail!("field count mismatch: world={}", vanilla_world.number);
+            }
+            for (vanilla_field, target_field) in
+                vanilla_world.fields.iter().zip(&target_world.fields)
+            {
+                let mut field_number = vanilla_field.number();
+                if field_number == FieldNumber::SurfaceNight {
+                    field_number = FieldNumber::Surface;
+                }
+                if vanilla_field.maps.len() != target_field.maps.len() {
+                    bail!("map count mismatch: field={:?}", field_number);
+                }
+                for (vanilla_map, target_map) in vanilla_field.maps.iter().zip(&target_field.maps) {
+                    for obj in &vanilla_map.objects {
+                        zelf.insert(field_number, obj, target_map)?;
+                    }
+                }
+            }
+        }
+        Ok(zelf)
+    }
+
+    fn insert(&mut self, field_number: FieldNumber, old_obj: &Object, map: &Map) -> Result<()> {
+        let found = || {
+            find_placed_item(map, old_obj)
+                .ok_or_else(|| anyhow!("item not found at ({}, {})", old_obj.x(), old_obj.y()))
+        };
+        match old_obj {
+            Object::Chest(obj) => {
+                let content = match obj.item() {
+                    ChestItem::None(_)
+                    | ChestItem::Equipment(Equipment {
+                        content: enums::Equipment::SweetClothing,
+                        ..
+                    }) => return Ok(()),
+                    ChestItem::Equipment(x) => enums::ChestItem::Equipment(x.content),
+                    ChestItem::Rom(x) => enums::ChestItem::Rom(x.content),
+                };
+                insert_once(&mut self.chests, (field_number, content), found)
+            }
+            Object::SubWeapon(obj) => {
+                let key = (field_number, obj.sub_weapon().content);
+                insert_once(&mut self.sub_weapons, key, found)
+            }
+            Object::MainWeapon(obj) => {
+                insert_once(&mut self.main_weapons, obj.main_weapon().content, found)
+            }
+            Object::Seal(obj) => insert_once(&mut self.seals, obj.seal().content, found),
+            Object::Rom(obj) => insert_once(&mut self.roms, obj.rom().content, found),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn item_shops(script: &Script) -> Result<Vec<ItemShop>> {
+    script
+        .shops()
+        .filter_map(|x| ItemShop::try_from_shop_object(x, &script.talks).transpose())
+        .collect()
+}
+
+/// Talk numbers of the storytellers and the important talks of the eldest.
+fn item_talk_numbers(script: &Script) -> Result<Vec<u16>> {
+    Ok(script
+        .shops()
+        .map(|x| Shop::try_from_shop_object(x, &script.talks))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter_map(|x| match x {
+            Shop::Storyteller(x) => Some(vec![x.talk_number()]),
+            Shop::ItemShop(_) => None,
+            Shop::Eldest(x) => Some(x.into_important_talk_numbers()),
+        })
+        .flatten()
+        .collect())
+}
+
+/// Reconstructs the placements of a script.dat produced by the editors.
+///
+/// `source` is the unshuffled storage of the game structure.
+/// Spots are located in the vanilla script, and items are identified by the flags they set.
+pub fn decompile(vanilla: &Script, target: &Script, source: &Storage) -> Result<Storage> {
+    let mut pool = ItemPool::new(vanilla, source)?;
+    let mut placements = Placements::new(vanilla, target)?;
+    let mut storage = source.clone();
+
+    for (content, x) in &mut storage.main_weapons {
+        let key = placements.main_weapons.remove(content);
+        let key = key.ok_or_else(|| anyhow!("spot not found: {}", x.spot))?;
+        x.item = pool.take(key)?;
+    }
+    for (content, x) in &mut storage.sub_weapons {
+        let key = placements.sub_weapons.remove(content);
+        let key = key.ok_or_else(|| anyhow!("spot not found: {}", x.spot))?;
+        x.item = pool.take(key)?;
+    }
+    for (content, x) in &mut storage.chests {
+        let key = placements.chests.remove(content);
+        let key = key.ok_or_else(|| anyhow!("spot not found: {}", x.spot))?;
+        x.item = pool.take(key)?;
+    }
+    for (content, x) in &mut storage.seals {
+        let key = placements.seals.remove(content);
+        let key = key.ok_or_else(|| anyhow!("spot not found: {}", x.spot))?;
+        x.item = pool.take(key)?;
+    }
+    for (content, x) in &mut storage.roms {
+        let key = placements.roms.remove(content);
+        let key = key.ok_or_else(|| anyhow!("spot not found: {}", x.spot))?;
+        x.item = pool.take(key)?;
+    }
+
+    let vanilla_shops = item_shops(vanilla)?;
+    for x in &mut storage.shops {
+        let Some(vanilla_shop) = vanilla_shops.iter().find(|shop| {
+            let old = ShopItem::to_spot_shop_items(shop.items());
+            enums::ShopItem::matches_items(old, x.spot.items())
+        }) else {
+            bail!("spot not found: {}", x.spot)
+        };
+        let talk_number = vanilla_shop.item_data_talk_number() as usize;
+        let Some(talk) = target.talks.get(talk_number) else {
+            bail!("script broken: talk_number={}", talk_number)
+        };
+        let items = shop_items_data::parse(talk)?;
+        let item = match x.idx {
+            0 => &items.0,
+            1 => &items.1,
+            2 => &items.2,
+            _ => bail!("invalid shop item index: {}", x.idx),
+        };
+        x.item = pool.take(ItemKey::from_shop_item(item))?;
+    }
+
+    let vanilla_talk_items = item_talk_numbers(vanilla)?
+        .into_iter()
+        .map(|number| Ok((number, vanilla.talks[number as usize].item()?)))
+        .collect::<Result<Vec<_>>>()?;
+    for x in &mut storage.talks {
+        let talk_number = vanilla_talk_items
+            .iter()
+            .find(|(_, item)| matches!(item, Some((item, _)) if *item == x.spot.item()))
+            .map(|&(number, _)| number as usize)
+            .ok_or_else(|| anyhow!("spot not found: {}", x.spot))?;
+        let Some(talk) = target.talks.get(talk_number) else {
+            bail!("script broken: talk_number={}", talk_number)
+        };
+        let Some((item, flag)) = talk.item()? else {
+            bail!("item not found in talk: {}", talk_number)
+        };
+        x.item = pool.take(ItemKey::from_talk_item(item, flag))?;
+    }
+
+    Ok(storage)
+}
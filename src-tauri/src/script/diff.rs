@@ -0,0 +1,346 @@
+use std::fmt::Write;
+
+use num_traits::FromPrimitive;
+
+use crate::script::{
+    data::{
+        flags::ObjectLocation,
+        object::{Object, Start, UnknownObject},
+        script::{Field, Map, Script},
+        talk::Talk,
+    },
+    enums::FieldNumber,
+};
+
+/// The parts of an object the diff compares, whatever its type.
+#[derive(PartialEq)]
+struct ObjectView {
+    number: u16,
+    x: i32,
+    y: i32,
+    ops: [i32; 4],
+    starts: Vec<(u32, bool)>,
+}
+
+impl ObjectView {
+    fn new(number: u16, x: i32, y: i32, ops: [i32; 4], starts: &[Start]) -> Self {
+        let starts = starts.iter().map(|x| (x.flag, x.run_when)).collect();
+        Self {
+            number,
+            x,
+            y,
+            ops,
+            starts,
+        }
+    }
+
+    fn from_object(obj: &Object) -> Self {
+        let ops = [obj.op1(), obj.op2(), obj.op3(), obj.op4()];
+        Self::new(obj.number(), obj.x(), obj.y(), ops, obj.starts())
+    }
+
+    fn from_unknown_object(obj: &UnknownObject) -> Self {
+        let ops = [obj.op1, obj.op2, obj.op3, obj.op4];
+        Self::new(obj.number, obj.x, obj.y, ops, &obj.starts)
+    }
+
+    fn same_place(&self, other: &Self) -> bool {
+        (self.number, self.x, self.y) == (other.number, other.x, other.y)
+    }
+}
+
+fn format_ops(ops: &[i32; 4]) -> String {
+    ops.map(|x| x.to_string()).join(",")
+}
+
+fn format_start(start: &(u32, bool)) -> String {
+    format!("{}={}", start.0, start.1 as u8)
+}
+
+fn format_starts(starts: &[(u32, bool)]) -> String {
+    starts
+        .iter()
+        .map(format_start)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn field_name(world: u8, field: u8) -> String {
+    match FieldNumber::from_u8(field) {
+        Some(number) => format!("world {} field {} ({:?})", world, field, number),
+        None => format!("world {} field {}", world, field),
+    }
+}
+
+fn map_name(world: u8, field: u8, (a, b, c): (u8, u8, u8)) -> String {
+    format!("{} map {},{},{}", field_name(world, field), a, b, c)
+}
+
+fn indent(text: &str) -> String {
+    text.replace('\n', "\n    ")
+}
+
+fn describe_change(old: &ObjectView, new: &ObjectView) -> String {
+    let mut text = String::new();
+    for (i, (a, b)) in old.ops.iter().zip(&new.ops).enumerate() {
+        if a != b {
+            write!(text, "\n  op{}: {} -> {}", i + 1, a, b).unwrap();
+        }
+    }
+    for start in old.starts.iter().filter(|x| !new.starts.contains(x)) {
+        write!(text, "\n  start removed: {}", format_start(start)).unwrap();
+    }
+    for start in new.starts.iter().filter(|x| !old.starts.contains(x)) {
+        write!(text, "\n  start added: {}", format_start(start)).unwrap();
+    }
+    if text.is_empty() {
+        // Same set of conditions in another order.
+        write!(
+            text,
+            "\n  starts: {} -> {}",
+            format_starts(&old.starts),
+            format_starts(&new.starts)
+        )
+        .unwrap();
+    }
+    text
+}
+
+/// Pairs unchanged objects first, then objects of the same number at the same place.
+/// Whatever is left over was removed or added.
+fn diff_objects(
+    location: impl Fn(usize, &ObjectView) -> ObjectLocation,
+    old: &[ObjectView],
+    new: &[ObjectView],
+    diffs: &mut Vec<String>,
+) {
+    let mut new_used = vec![false; new.len()];
+    let mut old_used = vec![false; old.len()];
+    for (i, a) in old.iter().enumerate() {
+        let found = (0..new.len()).find(|&j| !new_used[j] && new[j] == *a);
+        if let Some(j) = found {
+            new_used[j] = true;
+            old_used[i] = true;
+        }
+    }
+    for (i, a) in old.iter().enumerate() {
+        if old_used[i] {
+            continue;
+        }
+        let found = (0..new.len()).find(|&j| !new_used[j] && new[j].same_place(a));
+        let Some(j) = found else {
+            diffs.push(format!(
+                "{} removed: {} starts [{}]",
+                location(i, a),
+                format_ops(&a.ops),
+                format_starts(&a.starts)
+            ));
+            continue;
+        };
+        new_used[j] = true;
+        diffs.push(format!(
+            "{} changed:{}",
+            location(i, a),
+            describe_change(a, &new[j])
+        ));
+    }
+    for (j, b) in new.iter().enumerate() {
+        if !new_used[j] {
+            diffs.push(format!(
+                "{} added: {} starts [{}]",
+                location(j, b),
+                format_ops(&b.ops),
+                format_starts(&b.starts)
+            ));
+        }
+    }
+}
+
+fn diff_talks(old: &[Talk], new: &[Talk], diffs: &mut Vec<String>) {
+    for i in 0..old.len().max(new.len()) {
+        let old = old.get(i).map(|x| x.to_readable());
+        let new = new.get(i).map(|x| x.to_readable());
+        match (old, new) {
+            (Some(a), Some(b)) if a == b => {}
+            (Some(a), Some(b)) => diffs.push(format!(
+                "talk {} changed:\n  - {}\n  + {}",
+                i,
+                indent(&a),
+                indent(&b)
+            )),
+            (Some(a), None) => diffs.push(format!("talk {} removed:\n  - {}", i, indent(&a))),
+            (None, Some(b)) => diffs.push(format!("talk {} added:\n  + {}", i, indent(&b))),
+            (None, None) => unreachable!(),
+        }
+    }
+}
+
+fn diff_map(world: u8, field: u8, old: &Map, new: &Map, diffs: &mut Vec<String>) {
+    let name = map_name(world, field, old.attrs);
+    for (direction, a, b) in [
+        ("up", old.up, new.up),
+        ("right", old.right, new.right),
+        ("down", old.down, new.down),
+        ("left", old.left, new.left),
+    ] {
+        if a != b {
+            diffs.push(format!("{} {}: {:?} -> {:?}", name, direction, a, b));
+        }
+    }
+    let location = |index, obj: &ObjectView| ObjectLocation {
+        world,
+        field,
+        map: Some(old.attrs),
+        index,
+        number: obj.number,
+        x: obj.x,
+        y: obj.y,
+    };
+    let old: Vec<_> = old.objects.iter().map(ObjectView::from_object).collect();
+    let new: Vec<_> = new.objects.iter().map(ObjectView::from_object).collect();
+    diff_objects(location, &old, &new, diffs);
+}
+
+fn diff_field(world: u8, old: &Field, new: &Field, diffs: &mut Vec<String>) {
+    let number = old.attrs.0;
+    let name = field_name(world, number);
+    if old.attrs != new.attrs {
+        diffs.push(format!("{}: {:?} -> {:?}", name, old.attrs, new.attrs));
+    }
+    if (old.chip_line, &old.hits, &old.animes) != (new.chip_line, &new.hits, &new.animes) {
+        diffs.push(format!("{}: chip line, hits or animes changed", name));
+    }
+    let location = |index, obj: &ObjectView| ObjectLocation {
+        world,
+        field: number,
+        map: None,
+        index,
+        number: obj.number,
+        x: obj.x,
+        y: obj.y,
+    };
+    let old_objects: Vec<_> = old
+        .objects
+        .iter()
+        .map(ObjectView::from_unknown_object)
+        .collect();
+    let new_objects: Vec<_> = new
+        .objects
+        .iter()
+        .map(ObjectView::from_unknown_object)
+        .collect();
+    diff_objects(location, &old_objects, &new_objects, diffs);
+
+    for map in &old.maps {
+        match new.maps.iter().find(|x| x.attrs == map.attrs) {
+            Some(new_map) => diff_map(world, number, map, new_map, diffs),
+            None => diffs.push(format!("{} removed", map_name(world, number, map.attrs))),
+        }
+    }
+    for map in &new.maps {
+        if !old.maps.iter().any(|x| x.attrs == map.attrs) {
+            diffs.push(format!("{} added", map_name(world, number, map.attrs)));
+        }
+    }
+}
+
+/// Object-level differences between two scripts, one entry per difference.
+/// Fields are matched by number and maps by their attributes.
+pub fn diff(old: &Script, new: &Script) -> Vec<String> {
+    let mut diffs = Vec::new();
+    diff_talks(&old.talks, &new.talks, &mut diffs);
+    for world in &old.worlds {
+        let Some(new_world) = new.worlds.iter().find(|x| x.number == world.number) else {
+            diffs.push(format!("world {} removed", world.number));
+            continue;
+        };
+        for field in &world.fields {
+            let new_field = new_world.fields.iter().find(|x| x.attrs.0 == field.attrs.0);
+            match new_field {
+                Some(new_field) => diff_field(world.number, field, new_field, &mut diffs),
+                None => diffs.push(format!(
+                    "{} removed",
+                    field_name(world.number, field.attrs.0)
+                )),
+            }
+        }
+        for field in &new_world.fields {
+            if !world.fields.iter().any(|x| x.attrs.0 == field.attrs.0) {
+                diffs.push(format!("{} added", field_name(world.number, field.attrs.0)));
+            }
+        }
+    }
+    for world in &new.worlds {
+        if !old.worlds.iter().any(|x| x.number == world.number) {
+            diffs.push(format!("world {} added", world.number));
+        }
+    }
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script::data::script::World;
+
+    use super::*;
+
+    fn start(flag: u32, run_when: bool) -> Start {
+        Start { flag, run_when }
+    }
+
+    fn script(talk: &str, objects: Vec<Object>) -> Script {
+        Script {
+            talks: vec![Talk::from_text("A"), Talk::from_text(talk)],
+            worlds: vec![World {
+                number: 0,
+                fields: vec![Field {
+                    attrs: (1, 0, 0, 0, 0),
+                    chip_line: (0, 0),
+                    hits: vec![],
+                    animes: vec![],
+                    objects: vec![],
+                    maps: vec![Map {
+                        attrs: (3, 1, 0),
+                        up: (0, 0, 0, 0),
+                        right: (0, 0, 0, 0),
+                        down: (0, 0, 0, 0),
+                        left: (0, 0, 0, 0),
+                        objects,
+                    }],
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_diff() -> anyhow::Result<()> {
+        let old = script(
+            "B",
+            vec![
+                Object::new(20, 1, 2, 3, 4, 5, 6, vec![start(58, true)])?,
+                Object::new(20, 10, 20, 0, 0, 0, 0, vec![])?,
+                Object::new(21, 0, 0, 0, 0, 0, 0, vec![])?,
+            ],
+        );
+        assert!(diff(&old, &old).is_empty());
+
+        let new = script(
+            "B\nC",
+            vec![
+                Object::new(22, 0, 0, 0, 0, 0, 0, vec![])?,
+                Object::new(20, 10, 20, 0, 0, 0, 0, vec![])?,
+                Object::new(20, 1, 2, 3, 7, 5, 6, vec![start(59, false)])?,
+            ],
+        );
+        assert_eq!(
+            diff(&old, &new),
+            [
+                "talk 1 changed:\n  - B\n  + B\n    C",
+                "world 0 field 1 (Surface) map 3,1,0 #0 object 20 at (1, 2) changed:\n  op2: 4 -> 7\n  start removed: 58=1\n  start added: 59=0",
+                "world 0 field 1 (Surface) map 3,1,0 #2 object 21 at (0, 0) removed: 0,0,0,0 starts []",
+                "world 0 field 1 (Surface) map 3,1,0 #0 object 22 at (0, 0) added: 0,0,0,0 starts []",
+            ]
+        );
+        Ok(())
+    }
+}
@@ -1,30 +1,40 @@
 pub mod add_starting_items;
 mod objects_factory;
+pub mod patch_rules;
 mod replace_talk_items;
 mod script_editor;
 mod talks_editor;
 
 use std::ops::Deref;
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 
-use crate::randomizer::storage::Storage;
+use crate::{dataset::spot::ShopSpot, randomizer::storage::Storage};
 
-use super::data::{object::ItemShop, script::Script};
+use super::{
+    data::{flags::FlagAllocator, object::ItemShop, script::Script, shop_items_data::ShopItem},
+    enums,
+};
 
 use {
-    replace_talk_items::replace_talk_items, script_editor::replace_items,
-    talks_editor::replace_shops,
+    replace_talk_items::replace_talk_items,
+    script_editor::replace_items,
+    talks_editor::{add_seed_banner, replace_shops, shop_description_talk_numbers},
 };
 
+fn item_shops(script: &Script) -> Result<Vec<ItemShop>> {
+    script
+        .shops()
+        .filter_map(|x| ItemShop::try_from_shop_object(x, &script.talks).transpose())
+        .collect()
+}
+
 pub fn apply_storage(script: &mut Script, shuffled: &Storage) -> Result<()> {
+    let mut flags = FlagAllocator::new(script)?;
     let mut worlds = script.worlds.clone();
-    replace_items(&mut worlds, script.deref(), shuffled)?;
+    replace_items(&mut worlds, script.deref(), shuffled, &mut flags)?;
 
-    let shops: Vec<_> = script
-        .shops()
-        .filter_map(|x| ItemShop::try_from_shop_object(x, &script.talks).transpose())
-        .collect::<Result<_>>()?;
+    let shops = item_shops(script)?;
     let mut talks = script.talks.clone();
     replace_shops(&mut talks, script.deref(), &shops, &shuffled.shops)?;
     replace_talk_items(&mut talks, script.deref(), &shuffled.talks)?;
@@ -32,3 +42,40 @@ pub fn apply_storage(script: &mut Script, shuffled: &Storage) -> Result<()> {
     script.talks = talks;
     Ok(())
 }
+
+/// The item data talk of the shop at `spot`.
+/// The shop is found by its vanilla items, so this must run before `apply_storage`.
+pub fn find_shop_talk_number(script: &Script, spot: &ShopSpot) -> Result<u16> {
+    item_shops(script)?
+        .iter()
+        .find(|x| {
+            enums::ShopItem::matches_items(ShopItem::to_spot_shop_items(x.items()), spot.items())
+        })
+        .map(|x| x.item_data_talk_number())
+        .ok_or_else(|| anyhow!("shop not found: {}", spot))
+}
+
+/// The most lines an item description of a shop has in `script`.
+/// The game shows the vanilla descriptions in full, so one of no more lines fits the window.
+pub fn shop_description_line_limit(script: &Script) -> Result<usize> {
+    let mut line_limit = 0;
+    for shop in item_shops(script)? {
+        for talk_number in shop_description_talk_numbers(shop.item_data_talk_number()) {
+            let Some(talk) = script.talks.get(talk_number) else {
+                bail!("script broken: talk_number={}", talk_number)
+            };
+            line_limit = line_limit.max(talk.to_string().lines().count());
+        }
+    }
+    Ok(line_limit)
+}
+
+/// Returns false if no item description of the shop has room for the banner.
+pub fn apply_seed_banner(
+    script: &mut Script,
+    shop_talk_number: u16,
+    line_limit: usize,
+    banner: &str,
+) -> Result<bool> {
+    add_seed_banner(&mut script.talks, shop_talk_number, line_limit, banner)
+}
@@ -1,7 +1,11 @@
+use anyhow::Result;
+
 use crate::script::{
+    consts::{ONE_TIME_FLAG_RANGE, SAVE_FLAG_RANGE},
     data::{
+        flags::FlagAllocator,
         item,
-        object::{ChestObject, Object, Start, UnknownObject},
+        object::{ChestObject, Object, Start, TriggerObject, UnknownObject},
         script::{Field, Map, World},
     },
     enums::{Equipment, Rom, SubWeapon},
@@ -9,47 +13,27 @@ use crate::script::{
 
 pub fn add_starting_items(
     worlds: Vec<World>,
+    flags: &mut FlagAllocator,
     equipment_list: &[Equipment],
     rom_list: &[Rom],
     sub_weapon_list: &[SubWeapon],
-) -> Vec<World> {
-    let unused_one_time_flag_no = 7400;
-    let unused_save_flag_no = 6000;
+) -> Result<Vec<World>> {
+    let unused_one_time_flag_no = flags.allocate(ONE_TIME_FLAG_RANGE)?;
+    let unused_save_flag_no = flags.allocate(SAVE_FLAG_RANGE)?;
     let x = 26624;
     let y = 14336;
     let starting_items: Vec<_> = [
         // // money
-        // Object::Unknown(UnknownObject {
-        //     number: 7,
-        //     x: 43008,
-        //     y: 22528,
-        //     op1: 7,
-        //     op2: 999,
-        //     op3: -1,
-        //     op4: -1,
-        //     starts: vec![],
-        // }),
+        // Object::ItemGiver(ItemGiverObject::new(43008, 22528, 7, 999, [-1, -1], vec![])),
         // // weights
-        // Object::Unknown(UnknownObject {
-        //     number: 7,
-        //     x: 43008,
-        //     y: 22528,
-        //     op1: 6,
-        //     op2: 999,
-        //     op3: -1,
-        //     op4: -1,
-        //     starts: vec![],
-        // }),
-        Object::Unknown(UnknownObject {
-            number: 22,
-            x: 26624,
-            y: 10240,
-            op1: 2,
-            op2: 2,
-            op3: unused_one_time_flag_no,
-            op4: -1,
-            starts: vec![],
-        }),
+        // Object::ItemGiver(ItemGiverObject::new(43008, 22528, 6, 999, [-1, -1], vec![])),
+        Object::Trigger(TriggerObject::new(
+            26624,
+            10240,
+            unused_one_time_flag_no,
+            [2, 2, -1],
+            vec![],
+        )),
     ]
     .into_iter()
     .chain(sub_weapon_list.iter().flat_map(|sub_weapon| {
@@ -63,7 +47,7 @@ pub fn add_starting_items(
                 op3: unused_save_flag_no as i32,
                 op4: -1,
                 starts: vec![Start {
-                    flag: unused_save_flag_no,
+                    flag: unused_save_flag_no as u32,
                     run_when: false,
                 }],
             }),
@@ -76,7 +60,7 @@ pub fn add_starting_items(
                 op3: unused_save_flag_no as i32,
                 op4: -1,
                 starts: vec![Start {
-                    flag: unused_save_flag_no,
+                    flag: unused_save_flag_no as u32,
                     run_when: false,
                 }],
             }),
@@ -86,11 +70,11 @@ pub fn add_starting_items(
         Object::Chest(ChestObject::new(
             x,
             y,
-            unused_one_time_flag_no as u16,
+            unused_one_time_flag_no,
             item::ChestItem::Equipment(item::Equipment {
                 content: *equipment,
                 price: None,
-                flag: unused_save_flag_no as u16,
+                flag: unused_save_flag_no,
             }),
             -1,
             vec![],
@@ -100,18 +84,18 @@ pub fn add_starting_items(
         Object::Chest(ChestObject::new(
             x,
             y,
-            unused_one_time_flag_no as u16,
+            unused_one_time_flag_no,
             item::ChestItem::Rom(item::Rom {
                 content: *rom,
                 price: None,
-                flag: unused_save_flag_no as u16,
+                flag: unused_save_flag_no,
             }),
             -1,
             vec![],
         ))
     }))
     .collect();
-    worlds
+    Ok(worlds
         .into_iter()
         .map(|world| World {
             number: world.number,
@@ -147,5 +131,5 @@ pub fn add_starting_items(
                 })
                 .collect(),
         })
-        .collect()
+        .collect())
 }
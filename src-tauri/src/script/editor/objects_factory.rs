@@ -7,9 +7,12 @@ use object_factory::{
     sub_weapon,
 };
 
+use anyhow::Result;
+
 use crate::script::{
-    consts::MEMO_FLAG_BASE_NO,
+    consts::ONE_TIME_FLAG_RANGE,
     data::{
+        flags::FlagAllocator,
         item::{ChestItem, Item},
         object::{ChestObject, Object, RomObject},
     },
@@ -66,34 +69,38 @@ pub fn to_objects_for_chest(old_obj: &ChestObject, item: Item) -> Vec<Object> {
     }
 }
 
-pub fn to_objects_for_hand_scanner(old_obj: &RomObject, item: Item) -> Vec<Object> {
+pub fn to_objects_for_hand_scanner(
+    old_obj: &RomObject,
+    item: Item,
+    flags: &mut FlagAllocator,
+) -> Result<Vec<Object>> {
     if let Item::Rom(item) = item {
-        return vec![Object::Rom(rom(old_obj, item))];
+        return Ok(vec![Object::Rom(rom(old_obj, item))]);
     }
 
-    let open_flag = MEMO_FLAG_BASE_NO + old_obj.rom().content as u16;
+    let open_flag = flags.allocate(ONE_TIME_FLAG_RANGE)?;
     let memo = memo(old_obj, open_flag, item.flag());
     let x = old_obj.x();
     // Raise it by half a square,
     // because the location of the ROMs is half a square lower than the normal items.
     let y = old_obj.y() - 2048;
-    match item {
+    Ok(match item {
         Item::Equipment(item) => vec![
-            Object::Unknown(memo),
+            Object::Tablet(memo),
             Object::Chest(invisible_chest(x, y, open_flag, ChestItem::Equipment(item))),
         ],
         Item::SubWeapon(item) => vec![
-            Object::Unknown(memo),
+            Object::Tablet(memo),
             Object::SubWeapon(simple_sub_weapon(x, y, open_flag, item)),
         ],
         Item::Rom(_) => unreachable!(),
         Item::Seal(item) => vec![
-            Object::Unknown(memo),
+            Object::Tablet(memo),
             Object::Seal(simple_seal(x, y, open_flag, item)),
         ],
         Item::MainWeapon(item) => vec![
-            Object::Unknown(memo),
+            Object::Tablet(memo),
             Object::MainWeapon(simple_main_weapon(x, y, open_flag, item)),
         ],
-    }
+    })
 }
@@ -10,7 +10,7 @@ use crate::script::{
                 starts_with_replaced_flag, starts_without_old_flag,
             },
             ChestObject, MainWeaponObject, Object, RomObject, SealObject, Start, SubWeaponObject,
-            UnknownObject,
+            TabletObject,
         },
     },
 };
@@ -123,7 +123,7 @@ pub fn hidden_main_weapon(old_obj: &ChestObject, item: MainWeapon) -> MainWeapon
     MainWeaponObject::new(old_obj.x(), old_obj.y(), item, starts)
 }
 
-pub fn memo(old_obj: &RomObject, set_flag: u16, done_flag: u16) -> UnknownObject {
+pub fn memo(old_obj: &RomObject, set_flag: u16, done_flag: u16) -> TabletObject {
     let shadow_event = Start {
         flag: 99999,
         run_when: true,
@@ -157,14 +157,12 @@ pub fn memo(old_obj: &RomObject, set_flag: u16, done_flag: u16) -> UnknownObject
             starts.len()
         );
     }
-    UnknownObject {
-        number: 37,
-        x: old_obj.x(),
-        y: old_obj.y(),
-        op1: BLANK_TALK_NUMBER,
-        op2: set_flag as i32,
-        op3: -1,
-        op4: -1,
+    TabletObject::new(
+        old_obj.x(),
+        old_obj.y(),
+        BLANK_TALK_NUMBER,
+        Some(set_flag),
+        [-1, -1],
         starts,
-    }
+    )
 }
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    randomizer::storage::Storage,
+    script::{
+        data::{
+            item::Item,
+            object::{Object, Start},
+            patch_rules::{find, rules, FlagRef, ItemRef, Value},
+            script::Script,
+        },
+        enums::FieldNumber,
+    },
+};
+
+use super::script_editor::find_item_set_flag;
+
+impl ItemRef {
+    fn vanilla_flag(&self, ctx: &Context) -> Result<u16> {
+        match self {
+            Self::Talk(name) => {
+                let talk_item = Self::talk_item(name)?;
+                find_item_set_flag(ctx.script, talk_item)?
+                    .ok_or_else(|| anyhow!("talk not found: {:?}", talk_item))
+            }
+            Self::SubWeapon(name) => {
+                let sub_weapon = Self::sub_weapon(name)?;
+                let field = ctx.script.field(ctx.field_number);
+                let found = field.and_then(|x| {
+                    x.sub_weapons()
+                        .find(|x| x.sub_weapon().content == sub_weapon)
+                });
+                let obj = found.ok_or_else(|| anyhow!("sub_weapon not found: {}", sub_weapon))?;
+                Ok(obj.sub_weapon().flag)
+            }
+        }
+    }
+
+    fn placed_flag(&self, ctx: &Context) -> Result<u16> {
+        let src = match self {
+            Self::Talk(name) => {
+                let talk_item = Self::talk_item(name)?;
+                let talk = ctx
+                    .shuffled
+                    .talks
+                    .iter()
+                    .find(|x| x.spot.item() == talk_item);
+                &talk
+                    .ok_or_else(|| anyhow!("talk not found: {:?}", talk_item))?
+                    .item
+                    .src
+            }
+            Self::SubWeapon(name) => {
+                let key = (ctx.field_number, Self::sub_weapon(name)?);
+                let sub_weapon = ctx.shuffled.sub_weapons.get(&key);
+                &sub_weapon
+                    .ok_or_else(|| anyhow!("sub_weapon not found: {}", key.1))?
+                    .item
+                    .src
+            }
+        };
+        Ok(Item::new(src, ctx.script)?.flag())
+    }
+}
+
+impl Value {
+    fn resolve(&self, ctx: &Context) -> Result<i32> {
+        Ok(match self {
+            Self::Number(x) => *x,
+            Self::Flag(FlagRef::VanillaItemFlag(x)) => x.vanilla_flag(ctx)? as i32,
+            Self::Flag(FlagRef::PlacedItemFlag(x)) => x.placed_flag(ctx)? as i32,
+        })
+    }
+
+    fn resolve_flag(&self, ctx: &Context) -> Result<u16> {
+        let value = self.resolve(ctx)?;
+        u16::try_from(value).map_err(|_| anyhow!("invalid flag: {}", value))
+    }
+}
+
+/// What the values of the rules are resolved against.
+pub struct Context<'a> {
+    pub script: &'a Script,
+    pub shuffled: &'a Storage,
+    /// The field of the object, with the night surface taken as the surface.
+    pub field_number: FieldNumber,
+}
+
+/// Rewrites the object as its rule tells, if any.
+/// `replace_flag_map` is applied to the starts along with the flags of the rule, if the rule
+/// rewrites start flags.
+pub fn apply(
+    ctx: &Context,
+    field_number: FieldNumber,
+    map: (u8, u8, u8),
+    obj: &Object,
+    replace_flag_map: &HashMap<u16, u16>,
+) -> Result<Option<Object>> {
+    let rule = find(rules()?, field_number, map, obj);
+    let Some(rule) = rule.filter(|x| !x.set.is_empty()) else {
+        return Ok(None);
+    };
+    let set = &rule.set;
+    let context = |e: anyhow::Error| e.context(format!("rule {}", rule.name));
+    let op = |value: &Option<Value>, old: i32| {
+        value
+            .as_ref()
+            .map_or(Ok(old), |x| x.resolve(ctx))
+            .map_err(context)
+    };
+    // Without start flags in the rule, the starts are kept as they are, as the trap in the
+    // Mausoleum of the Giants has always been.
+    let starts = if set.start_flags.is_empty() {
+        obj.starts().to_vec()
+    } else {
+        let mut replace_flag_map = replace_flag_map.clone();
+        for replacement in &set.start_flags {
+            let from = replacement.from.resolve_flag(ctx).map_err(context)?;
+            let to = replacement.to.resolve_flag(ctx).map_err(context)?;
+            replace_flag_map.insert(from, to);
+        }
+        obj.starts()
+            .iter()
+            .map(|start| {
+                let flag = u16::try_from(start.flag)
+                    .ok()
+                    .and_then(|x| replace_flag_map.get(&x))
+                    .map_or(start.flag, |&x| x as u32);
+                Start {
+                    flag,
+                    run_when: start.run_when,
+                }
+            })
+            .collect()
+    };
+    let new_obj = Object::new(
+        obj.number(),
+        obj.x(),
+        obj.y(),
+        op(&set.op1, obj.op1())?,
+        op(&set.op2, obj.op2())?,
+        op(&set.op3, obj.op3())?,
+        op(&set.op4, obj.op4())?,
+        starts,
+    )?;
+    Ok(Some(new_obj))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script::data::{
+        patch_rules::{is_not_a_spot, unmatched_rules},
+        script::{Field, Map, World},
+    };
+
+    use super::*;
+
+    fn script(field_number: u8, objects: Vec<Object>) -> Script {
+        Script {
+            talks: vec![],
+            worlds: vec![World {
+                number: 0,
+                fields: vec![Field {
+                    attrs: (field_number, 0, 0, 0, 0),
+                    chip_line: (0, 0),
+                    hits: vec![],
+                    animes: vec![],
+                    objects: vec![],
+                    maps: vec![Map {
+                        attrs: (0, 0, 0),
+                        up: (0, 0, 0, 0),
+                        right: (0, 0, 0, 0),
+                        down: (0, 0, 0, 0),
+                        left: (0, 0, 0, 0),
+                        objects,
+                    }],
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_rules() -> Result<()> {
+        let start = |flag| Start {
+            flag,
+            run_when: true,
+        };
+        let vimana = Object::new(186, 10, 20, 0, 0, 0, 0, vec![start(788)])?;
+        let twin_statue = Object::new(1, 8192, 6144, 420, 14, 766, 0, vec![])?;
+        let script = script(
+            FieldNumber::TwinLabyrinthsLeft as u8,
+            vec![vimana.clone(), twin_statue.clone()],
+        );
+        let unmatched = unmatched_rules(&script)?;
+        assert_eq!(
+            unmatched,
+            ["mausoleumOfTheGiantsAnkhJewelTrap", "gateOfIllusionShops"]
+        );
+
+        let field_number = FieldNumber::TwinLabyrinthsLeft;
+        assert!(is_not_a_spot(
+            rules()?,
+            field_number,
+            (0, 0, 0),
+            &twin_statue
+        ));
+        assert!(!is_not_a_spot(rules()?, field_number, (0, 0, 0), &vimana));
+
+        let shuffled = Storage {
+            main_weapons: Default::default(),
+            sub_weapons: Default::default(),
+            chests: Default::default(),
+            seals: Default::default(),
+            roms: Default::default(),
+            talks: vec![],
+            shops: vec![],
+            events: vec![],
+        };
+        let ctx = Context {
+            script: &script,
+            shuffled: &shuffled,
+            field_number,
+        };
+        let replace_flag_map = HashMap::from([(788, 1), (500, 501)]);
+        let patched = apply(&ctx, field_number, (0, 0, 0), &vimana, &replace_flag_map)?.unwrap();
+        assert_eq!(patched.starts()[0].flag, 891);
+        assert_eq!((patched.x(), patched.y()), (10, 20));
+        assert!(apply(
+            &ctx,
+            field_number,
+            (0, 0, 0),
+            &twin_statue,
+            &replace_flag_map
+        )?
+        .is_none());
+        Ok(())
+    }
+}
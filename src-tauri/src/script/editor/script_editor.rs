@@ -7,6 +7,7 @@ use crate::{
     randomizer::storage::Storage,
     script::{
         data::{
+            flags::FlagAllocator,
             item::{ChestItem, Equipment, Item, Rom},
             object::{Object, Shop, Start, UnknownObject},
             script::{Script, World},
@@ -15,18 +16,14 @@ use crate::{
     },
 };
 
-use super::objects_factory::{
-    to_object_for_shutter, to_object_for_special_chest, to_objects_for_chest,
-    to_objects_for_hand_scanner,
+use super::{
+    objects_factory::{
+        to_object_for_shutter, to_object_for_special_chest, to_objects_for_chest,
+        to_objects_for_hand_scanner,
+    },
+    patch_rules::{self, Context},
 };
 
-fn fix_trap_of_mausoleum_of_the_giants(
-    obj: &mut UnknownObject,
-    prev_sub_weapon_shutter_item: &Item,
-) {
-    obj.op1 = prev_sub_weapon_shutter_item.flag() as i32;
-}
-
 pub fn find_item_set_flag(script: &Script, talk_item: enums::TalkItem) -> Result<Option<u16>> {
     Ok(script
         .shops()
@@ -46,21 +43,6 @@ pub fn find_item_set_flag(script: &Script, talk_item: enums::TalkItem) -> Result
         .map(|(_item, set_flag)| set_flag))
 }
 
-fn find_new_item_flag(
-    storage: &Storage,
-    script: &Script,
-    talk_item: enums::TalkItem,
-) -> Result<u16> {
-    let new_item_src = storage
-        .talks
-        .iter()
-        .find(|x| x.spot.item() == talk_item)
-        .ok_or_else(|| anyhow!("talk not found: {:?}", talk_item))?
-        .item
-        .src;
-    Ok(Item::new(&new_item_src, script)?.flag())
-}
-
 /// ROMs do not alter the environment. Therefore, the flags of ROMs can be replaced in a batch.
 fn replace_flag_map(shuffled: &Storage, script: &Script) -> Result<HashMap<u16, u16>> {
     shuffled
@@ -109,16 +91,35 @@ fn replace_all_flags(starts: &[Start], replace_flag_map: &HashMap<u16, u16>) ->
         .collect()
 }
 
+#[allow(clippy::too_many_arguments)]
 fn new_objs(
     obj: &Object,
-    mut field_number: enums::FieldNumber,
+    original_field_number: enums::FieldNumber,
+    map_attrs: (u8, u8, u8),
     next_objs: &[Object],
     script: &Script,
     shuffled: &Storage,
     replace_flag_map: &HashMap<u16, u16>,
+    flags: &mut FlagAllocator,
 ) -> Result<Vec<Object>> {
-    if field_number == enums::FieldNumber::SurfaceNight {
-        field_number = enums::FieldNumber::Surface;
+    let field_number = match original_field_number {
+        enums::FieldNumber::SurfaceNight => enums::FieldNumber::Surface,
+        field_number => field_number,
+    };
+    let ctx = Context {
+        script,
+        shuffled,
+        field_number,
+    };
+    let patched = patch_rules::apply(
+        &ctx,
+        original_field_number,
+        map_attrs,
+        obj,
+        replace_flag_map,
+    )?;
+    if let Some(obj) = patched {
+        return Ok(vec![obj]);
     }
     match obj {
         Object::Chest(chest_obj) => {
@@ -153,65 +154,27 @@ fn new_objs(
             if sub_weapon_obj.sub_weapon().content == enums::SubWeapon::AnkhJewel {
                 // Gate of Guidance
                 if sub_weapon_obj.sub_weapon().flag == 743 {
-                    let wall_check_flag = get_next_wall_check_flag(next_objs)
+                    let open_flag = get_next_wall_check_flag(next_objs)?
                         .ok_or(anyhow!("wall_check_flag not found"))?;
-                    let open_flag = u16::try_from(wall_check_flag)?;
                     return Ok(vec![to_object_for_shutter(obj, open_flag, item)]);
                 }
                 return Ok(vec![to_object_for_special_chest(obj, item)]);
             }
             let open_flag = if sub_weapon_obj.sub_weapon().content == enums::SubWeapon::Pistol {
-                get_next_breakable_wall_check_flag(next_objs)?
+                get_next_breakable_wall_check_flag(next_objs)
             } else {
-                get_next_shutter_check_flag(next_objs)?
-            }
+                get_next_shutter_check_flag(next_objs)
+            }?
             .ok_or(anyhow!("next_shutter_check_flag not found"))?;
             Ok(vec![to_object_for_shutter(obj, open_flag, item)])
         }
-        Object::Shop(_) => {
-            let starts = if field_number == enums::FieldNumber::GateOfIllusion {
-                // NOTE: Do not rewrite flags elsewhere, as the item must be effective.
-                let mut replace_flag_map = replace_flag_map.clone();
-
-                let pepper = enums::TalkItem::Equipment(enums::Equipment::Pepper);
-                let old_flag = find_item_set_flag(script, pepper)?
-                    .ok_or_else(|| anyhow!("talk not found: {:?}", pepper))?;
-                let new_flag = find_new_item_flag(shuffled, script, pepper)?;
-                replace_flag_map.insert(old_flag, new_flag);
-                let anchor = enums::TalkItem::Equipment(enums::Equipment::Anchor);
-                let old_flag = find_item_set_flag(script, anchor)?
-                    .ok_or_else(|| anyhow!("talk not found: {:?}", anchor))?;
-                let new_flag = find_new_item_flag(shuffled, script, anchor)?;
-                replace_flag_map.insert(old_flag, new_flag);
-
-                let mini_doll = enums::TalkItem::Equipment(enums::Equipment::MiniDoll);
-                let old_flag = find_item_set_flag(script, mini_doll)?
-                    .ok_or_else(|| anyhow!("talk not found: {:?}", mini_doll))?;
-                let new_flag = find_new_item_flag(shuffled, script, mini_doll)?;
-                replace_flag_map.insert(old_flag, new_flag);
-
-                replace_all_flags(obj.starts(), &replace_flag_map)
-            } else {
-                replace_all_flags(obj.starts(), replace_flag_map)
-            };
-            Ok(vec![Object::Unknown(UnknownObject {
-                number: obj.number(),
-                x: obj.x(),
-                y: obj.y(),
-                op1: obj.op1(),
-                op2: obj.op2(),
-                op3: obj.op3(),
-                op4: obj.op4(),
-                starts,
-            })])
-        }
         Object::Rom(rom_obj) => {
             let Some(rom) = shuffled.roms.get(&rom_obj.rom().content) else {
                 debug!("rom not found: {}", rom_obj.rom().content);
                 return Ok(vec![obj.clone()]);
             };
             let item = Item::new(&rom.item.src, script)?;
-            Ok(to_objects_for_hand_scanner(rom_obj, item))
+            to_objects_for_hand_scanner(rom_obj, item, flags)
         }
         Object::Seal(seal_obj) => {
             let Some(seal) = shuffled.seals.get(&seal_obj.seal().content) else {
@@ -233,48 +196,40 @@ fn new_objs(
                 .ok_or(anyhow!("next_shutter_check_flag not found"))?;
             Ok(vec![to_object_for_shutter(obj, open_flag, item)])
         }
+        // apply ROMs replacement
+        Object::MapRewrite(map_rewrite_obj) => {
+            let mut obj = map_rewrite_obj.clone();
+            if let Some(&flag) = replace_flag_map.get(&obj.check_flag()) {
+                obj.set_check_flag(flag);
+            }
+            Ok(vec![Object::MapRewrite(obj)])
+        }
+        // Rebuilt with the new starts so that they stay typed for the later editors.
+        Object::Shop(_)
+        | Object::ItemGiver(_)
+        | Object::Shutter(_)
+        | Object::Trigger(_)
+        | Object::Tablet(_)
+        | Object::BreakableWall(_)
+        | Object::Trap(_) => {
+            let starts = replace_all_flags(obj.starts(), replace_flag_map);
+            let (x, y) = (obj.x(), obj.y());
+            let (op1, op2, op3, op4) = (obj.op1(), obj.op2(), obj.op3(), obj.op4());
+            Ok(vec![Object::new(
+                obj.number(),
+                x,
+                y,
+                op1,
+                op2,
+                op3,
+                op4,
+                starts,
+            )?])
+        }
         Object::Unknown(unknown_obj) => {
             match unknown_obj.number {
                 // Chests | Sub weapons | Shops | Roms | Seals | Main weapons
                 1 | 13 | 14 | 32 | 71 | 77 => unreachable!(),
-                // 59: Map rewrite
-                // apply ROMs replacement
-                59 => Ok(vec![Object::Unknown(UnknownObject {
-                    number: obj.number(),
-                    x: obj.x(),
-                    y: obj.y(),
-                    op1: obj.op1(),
-                    op2: obj.op2(),
-                    op3: replace_flag_map
-                        .get(&u16::try_from(obj.op3())?)
-                        .copied()
-                        .unwrap_or(obj.op3() as u16) as i32,
-                    op4: obj.op4(),
-                    starts: obj.starts().to_vec(),
-                })]),
-                // Trap object for the Ankh Jewel Treasure Chest in Mausoleum of the Giants.
-                // It is made to work correctly when acquiring items.
-                140 if unknown_obj.x == 49152 && unknown_obj.y == 16384 => {
-                    let key = (field_number, enums::SubWeapon::AnkhJewel);
-                    let Some(sub_weapon) = shuffled.sub_weapons.get(&key) else {
-                        bail!("sub_weapon not found")
-                    };
-                    let mut obj = unknown_obj.clone();
-                    let prev_sub_weapon_shutter_item = &Item::new(&sub_weapon.item.src, script)?;
-                    fix_trap_of_mausoleum_of_the_giants(&mut obj, prev_sub_weapon_shutter_item);
-                    Ok(vec![Object::Unknown(obj)])
-                }
-                // ヴィマーナは飛行機模型を取得したら出現しないようになっている。
-                // 飛行機模型取得後に飛行機模型の宝箱を開けられるように、飛行機模型出現のフラグに変更する。
-                186 if unknown_obj.starts.len() == 1 && unknown_obj.starts[0].flag == 788 => {
-                    Ok(vec![Object::Unknown(UnknownObject {
-                        starts: vec![Start {
-                            flag: 891,
-                            run_when: unknown_obj.starts[0].run_when,
-                        }],
-                        ..unknown_obj.clone()
-                    })])
-                }
                 _ => Ok(vec![Object::Unknown(UnknownObject {
                     number: obj.number(),
                     x: obj.x(),
@@ -290,7 +245,12 @@ fn new_objs(
     }
 }
 
-pub fn replace_items(worlds: &mut [World], script: &Script, shuffled: &Storage) -> Result<()> {
+pub fn replace_items(
+    worlds: &mut [World],
+    script: &Script,
+    shuffled: &Storage,
+    flags: &mut FlagAllocator,
+) -> Result<()> {
     let replace_flag_map = replace_flag_map(shuffled, script)?;
     for world in worlds {
         for field in &mut world.fields {
@@ -301,10 +261,12 @@ pub fn replace_items(worlds: &mut [World], script: &Script, shuffled: &Storage)
                     objects.append(&mut new_objs(
                         &map.objects[i],
                         field_number,
+                        map.attrs,
                         &map.objects[i + 1..],
                         script,
                         shuffled,
                         &replace_flag_map,
+                        flags,
                     )?);
                 }
                 map.objects = objects;
@@ -314,24 +276,56 @@ pub fn replace_items(worlds: &mut [World], script: &Script, shuffled: &Storage)
     Ok(())
 }
 
+/// The first object of `number` after the item. One that did not parse as the expected type
+/// is an error rather than skipped, so that a later object is not taken by mistake.
+fn find_next<'a, T>(
+    objs: &'a [Object],
+    number: u16,
+    typed: impl Fn(&'a Object) -> Option<T>,
+) -> Result<Option<T>> {
+    let Some(obj) = objs.iter().find(|x| x.number() == number) else {
+        return Ok(None);
+    };
+    let ops = [obj.op1(), obj.op2(), obj.op3(), obj.op4()];
+    typed(obj)
+        .map(Some)
+        .ok_or_else(|| anyhow!("invalid object {}: {:?}", number, ops))
+}
+
 fn get_next_shutter_check_flag(objs: &[Object]) -> Result<Option<u16>> {
-    Ok(objs
-        .iter()
-        .find(|x| x.number() == 20)
-        .map(|x| u16::try_from(x.op1()))
-        .transpose()?)
+    find_next(objs, 20, |x| match x {
+        Object::Shutter(x) => Some(x.open_flag()),
+        _ => None,
+    })
 }
 
-fn get_next_wall_check_flag(objs: &[Object]) -> Option<i32> {
-    Some(objs.iter().find(|x| x.number() == 59)?.op3())
+fn get_next_wall_check_flag(objs: &[Object]) -> Result<Option<u16>> {
+    find_next(objs, 59, |x| match x {
+        Object::MapRewrite(x) => Some(x.check_flag()),
+        _ => None,
+    })
 }
 
 fn get_next_breakable_wall_check_flag(objs: &[Object]) -> Result<Option<u16>> {
-    objs.iter()
-        .find(|x| x.number() == 70)
-        .map(|x| {
-            let data = x.op4();
-            Ok(u16::try_from((data - (data / 10000) * 10000) / 10)?)
-        })
-        .transpose()
+    find_next(objs, 70, |x| match x {
+        Object::BreakableWall(x) => Some(x.check_flag()),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_next_shutter_check_flag() -> Result<()> {
+        let object = |number, op1| Object::new(number, 0, 0, op1, 0, 0, 0, vec![]);
+        let objs = [object(22, 0)?, object(20, 800)?, object(20, 801)?];
+        assert_eq!(get_next_shutter_check_flag(&objs)?, Some(800));
+        assert_eq!(get_next_shutter_check_flag(&objs[..1])?, None);
+        // Not the shutter after it.
+        let objs = [object(20, -1)?, object(20, 801)?];
+        assert!(get_next_shutter_check_flag(&objs).is_err());
+        Ok(())
+    }
 }
@@ -244,3 +244,29 @@ pub fn replace_shops(
     }
     Ok(())
 }
+
+/// The item descriptions of a shop follow its item data talk, one per item.
+pub fn shop_description_talk_numbers(shop_talk_number: u16) -> impl Iterator<Item = usize> {
+    (0..3).map(move |idx| shop_talk_number as usize + 1 + idx)
+}
+
+/// Appends the banner as a new line to the first item description of the shop that stays within
+/// `line_limit` lines with it. Returns false if none does.
+pub fn add_seed_banner(
+    talks: &mut [Talk],
+    shop_talk_number: u16,
+    line_limit: usize,
+    banner: &str,
+) -> Result<bool> {
+    for talk_number in shop_description_talk_numbers(shop_talk_number) {
+        let Some(talk) = talks.get_mut(talk_number) else {
+            bail!("script broken: talk_number={}", talk_number)
+        };
+        let text = talk.to_string();
+        if text.lines().count() < line_limit {
+            *talk = Talk::from_text(&format!("{}\n{}", text, hide_overflow(banner)));
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
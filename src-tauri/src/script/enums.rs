@@ -8,6 +8,19 @@ pub use {
     rom::Rom,
 };
 
+/// The names of the variants are in PascalCase, and the data files name them in camelCase.
+pub fn to_pascal_case(camel_case: &str) -> String {
+    let mut chars = camel_case.chars();
+    let first = chars.next().into_iter().flat_map(|x| x.to_uppercase());
+    first.chain(chars).collect()
+}
+
+pub fn to_camel_case(pascal_case: &str) -> String {
+    let mut chars = pascal_case.chars();
+    let first = chars.next().into_iter().flat_map(|x| x.to_lowercase());
+    first.chain(chars).collect()
+}
+
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 #[repr(u8)]
 pub enum ChestItem {
@@ -37,6 +50,6 @@ impl ShopItem {
     }
 
     pub fn matches(&self, right: Option<&Self>) -> bool {
-        right.map_or(true, |x| self == x)
+        right.is_none_or(|x| self == x)
     }
 }
@@ -1,3 +1,7 @@
 pub mod dat;
+pub mod patch;
 pub mod scriptconverter;
 pub mod scripttxtparser;
+// Only lmocodec reads and writes the structured form.
+#[allow(dead_code)]
+pub mod structured;
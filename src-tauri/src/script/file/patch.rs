@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use sha3::Digest;
+
+use super::scriptconverter::is_valid_script_dat;
+
+const MAGIC: &[u8; 8] = b"LMOPATCH";
+const VERSION: u8 = 1;
+const HASH_SIZE: usize = 64;
+/// Shorter matches are cheaper to insert than to copy.
+const BLOCK_SIZE: usize = 16;
+/// The target length comes from the patch, so it is not trusted for allocation.
+const MAX_PREALLOCATION: usize = 16 * 1024 * 1024;
+
+const OP_COPY: u8 = 0;
+const OP_INSERT: u8 = 1;
+
+fn hash(data: &[u8]) -> Vec<u8> {
+    sha3::Sha3_512::digest(data).to_vec()
+}
+
+fn write_varint(patch: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        patch.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    patch.push(value as u8);
+}
+
+fn read_varint(patch: &mut &[u8]) -> Result<usize> {
+    let mut value = 0usize;
+    for shift in (0..usize::BITS).step_by(7) {
+        let Some((&byte, rest)) = patch.split_first() else {
+            bail!("unexpected end of patch");
+        };
+        *patch = rest;
+        value |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("varint overflow")
+}
+
+fn read_bytes<'a>(patch: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if patch.len() < len {
+        bail!("unexpected end of patch");
+    }
+    let (bytes, rest) = patch.split_at(len);
+    *patch = rest;
+    Ok(bytes)
+}
+
+fn match_len(base: &[u8], target: &[u8]) -> usize {
+    base.iter().zip(target).take_while(|(a, b)| a == b).count()
+}
+
+fn flush_insert(patch: &mut Vec<u8>, literal: &[u8]) {
+    if literal.is_empty() {
+        return;
+    }
+    patch.push(OP_INSERT);
+    write_varint(patch, literal.len());
+    patch.extend_from_slice(literal);
+}
+
+/// Greedy block matching. The editors insert and remove text, so the copy source
+/// usually continues where the previous copy ended; that is tried before the index.
+fn encode(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut index: HashMap<&[u8], usize> = HashMap::new();
+    for (i, block) in base.windows(BLOCK_SIZE).enumerate() {
+        index.entry(block).or_insert(i);
+    }
+
+    let mut patch = Vec::new();
+    patch.extend_from_slice(MAGIC);
+    patch.push(VERSION);
+    patch.extend_from_slice(&hash(base));
+    patch.extend_from_slice(&hash(target));
+    write_varint(&mut patch, target.len());
+
+    let mut literal_start = 0;
+    let mut next_src = 0;
+    let mut i = 0;
+    while i < target.len() {
+        let rest = &target[i..];
+        let continued = base.get(next_src..).map_or(0, |base| match_len(base, rest));
+        let (src, len) = if continued >= BLOCK_SIZE {
+            (next_src, continued)
+        } else {
+            let found = rest
+                .get(..BLOCK_SIZE)
+                .and_then(|block| index.get(block))
+                .map(|&src| (src, match_len(&base[src..], rest)));
+            let Some(found) = found else {
+                i += 1;
+                continue;
+            };
+            found
+        };
+        flush_insert(&mut patch, &target[literal_start..i]);
+        patch.push(OP_COPY);
+        write_varint(&mut patch, src);
+        write_varint(&mut patch, len);
+        i += len;
+        literal_start = i;
+        next_src = src + len;
+    }
+    flush_insert(&mut patch, &target[literal_start..]);
+    patch
+}
+
+fn decode(base: &[u8], mut patch: &[u8]) -> Result<Vec<u8>> {
+    if read_bytes(&mut patch, MAGIC.len()).ok() != Some(MAGIC) {
+        bail!("not a patch file");
+    }
+    let version = read_bytes(&mut patch, 1)?[0];
+    if version != VERSION {
+        bail!("unsupported patch version: {}", version);
+    }
+    if read_bytes(&mut patch, HASH_SIZE)? != hash(base) {
+        bail!("the patch was made for another script.dat");
+    }
+    let target_hash = read_bytes(&mut patch, HASH_SIZE)?;
+    let target_len = read_varint(&mut patch)?;
+
+    let mut target = Vec::with_capacity(target_len.min(MAX_PREALLOCATION));
+    while !patch.is_empty() {
+        let op = read_bytes(&mut patch, 1)?[0];
+        match op {
+            OP_COPY => {
+                let src = read_varint(&mut patch)?;
+                let len = read_varint(&mut patch)?;
+                let bytes = src
+                    .checked_add(len)
+                    .and_then(|end| base.get(src..end))
+                    .ok_or_else(|| anyhow!("copy out of range: {}+{}", src, len))?;
+                target.extend_from_slice(bytes);
+            }
+            OP_INSERT => {
+                let len = read_varint(&mut patch)?;
+                target.extend_from_slice(read_bytes(&mut patch, len)?);
+            }
+            _ => bail!("unknown patch operation: {}", op),
+        }
+        if target.len() > target_len {
+            bail!("patch output is too long");
+        }
+    }
+    if target.len() != target_len {
+        bail!("patch output is too short");
+    }
+    if hash(&target) != target_hash {
+        bail!("patch output does not match");
+    }
+    Ok(target)
+}
+
+/// A diff of a randomized script.dat against the vanilla one.
+/// The patch contains no more of the vanilla file than the randomizer changed.
+pub fn make_patch(vanilla_script_dat: &[u8], script_dat: &[u8]) -> Result<Vec<u8>> {
+    if !is_valid_script_dat(vanilla_script_dat) {
+        bail!("Invalid script.dat file");
+    }
+    Ok(encode(vanilla_script_dat, script_dat))
+}
+
+/// Reproduces the randomized script.dat byte for byte.
+pub fn apply_patch(vanilla_script_dat: &[u8], patch: &[u8]) -> Result<Vec<u8>> {
+    if !is_valid_script_dat(vanilla_script_dat) {
+        bail!("Invalid script.dat file");
+    }
+    decode(vanilla_script_dat, patch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Vec<u8> {
+        let mut state = 1u32;
+        (0..4096)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_patch_round_trip() -> Result<()> {
+        let base = base();
+        let mut edited = base.clone();
+        edited.splice(100..110, b"replaced text".iter().copied());
+        edited.drain(2000..2100);
+        edited.extend_from_slice(&base[500..700]);
+        let cases = [base.clone(), edited, vec![], base[..10].to_vec()];
+        for target in cases {
+            let patch = encode(&base, &target);
+            assert_eq!(decode(&base, &patch)?, target);
+        }
+        assert!(encode(&base, &base).len() < 200);
+        Ok(())
+    }
+
+    #[test]
+    fn test_patch_rejects_mismatch() {
+        let base = base();
+        let mut target = base.clone();
+        target[1234] ^= 1;
+        let patch = encode(&base, &target);
+
+        let mut other_base = base.clone();
+        other_base[0] ^= 1;
+        assert!(decode(&other_base, &patch).is_err());
+
+        let mut broken = patch.clone();
+        *broken.last_mut().unwrap() ^= 1;
+        assert!(decode(&base, &broken).is_err());
+        assert!(decode(&base, &patch[..patch.len() - 1]).is_err());
+        assert!(decode(&base, b"LMOPATCH").is_err());
+    }
+
+    #[test]
+    fn test_patch_rejects_wrong_length() {
+        let base = base();
+        let target = base[..10].to_vec();
+        let header_len = MAGIC.len() + 1 + HASH_SIZE * 2;
+        let mut header = encode(&base, &target);
+        let ops = header.split_off(header_len + 1);
+        for target_len in [9, 11, usize::MAX] {
+            let mut patch = header[..header_len].to_vec();
+            write_varint(&mut patch, target_len);
+            patch.extend_from_slice(&ops);
+            assert!(decode(&base, &patch).is_err());
+        }
+    }
+}
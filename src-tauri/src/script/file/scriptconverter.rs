@@ -1,16 +1,29 @@
 use anyhow::{bail, Result};
 use sha3::Digest;
 
-use crate::script::data::script::Script;
+use crate::script::{data::script::Script, validation::validate};
 
 use super::dat::{cipher_to_text, text_to_cipher};
 
 const SCRIPT_DAT_HASH:&str = "d18f3a643bee62db6870b35b1a1781bcc4067bd7409fa620168e16054ddc7ce645463b59e06d0768d87eff9ad9bdc1f0efd04dbc498d2e5de73d5a863a692a90";
 const SCRIPT_DAT_EN_HASH:&str = "146e1b6e9e63ed22fb84b3c38f4d25a0723b07fe3fefe9395af68d6eeaa3b1108b288847ec50114efff4e7600afccc68a983d681b94cbb55a507b21f45d52db7";
 
+/// Accepts the known versions, and other versions that pass the structural validation.
 pub fn read_script_dat(file: &[u8]) -> Result<Script> {
+    let script = parse_script_dat(file)?;
     if !is_valid_script_dat(file) {
-        bail!("Invalid script.dat file");
+        let issues = validate(&script);
+        if !issues.is_empty() {
+            bail!("Unsupported script.dat file:\n{}", issues.join("\n"));
+        }
+    }
+    Ok(script)
+}
+
+/// Unlike `read_script_dat`, this accepts any script.dat files.
+pub fn parse_script_dat(file: &[u8]) -> Result<Script> {
+    if file.is_empty() {
+        bail!("Empty script.dat file");
     }
     let txt = cipher_to_text(file);
     Script::parse(&txt)
@@ -21,6 +34,14 @@ pub fn build_script_dat(script: &Script) -> Vec<u8> {
     text_to_cipher(&txt)
 }
 
+pub fn check_script_dat(file: &[u8]) -> Result<()> {
+    if is_valid_script_dat(file) {
+        return Ok(());
+    }
+    read_script_dat(file).map(|_| ())
+}
+
+/// Whether the file is one of the known versions.
 pub fn is_valid_script_dat(file: &[u8]) -> bool {
     let script_dat_hash = sha3::Sha3_512::digest(file).to_vec();
     script_dat_hash == hex::decode(SCRIPT_DAT_HASH).unwrap()
@@ -1,241 +1,320 @@
-use std::fmt::Write;
-
-use anyhow::{anyhow, bail, Result};
-use scraper::{node::Attributes, ElementRef, Html};
-
-use crate::script::{
-    data::{
-        object::{Object, Start, UnknownObject},
-        script::{Field, Map, World},
-        shop_items_data,
-        talk::Talk,
-    },
-    enums::SubWeapon,
+#[cfg(test)]
+mod html;
+
+use std::{collections::HashMap, fmt::Write};
+
+use anyhow::{anyhow, Result};
+
+use crate::script::data::{
+    object::{Object, Start, UnknownObject},
+    script::{Field, Map, World},
+    talk::Talk,
 };
 
-pub fn parse_script_txt(text: &str) -> Result<(Vec<Talk>, Vec<World>)> {
-    let parser = Html::parse_fragment(text);
-    let root = parser.root_element().child_elements().collect::<Vec<_>>();
-    // NOTE: scraper converts all tag names to lowercase
-    let talks: Vec<_> = root
-        .iter()
-        .filter(|x| x.value().name() == "talk")
-        .map(|x| {
-            let talk = x
-                .text()
-                .collect::<String>()
-                .trim_start_matches('\n')
-                .to_owned();
-            Talk::from_text(&talk)
+use super::dat::reverse_code_map;
+
+/// A tag such as `<OBJECT 1,2,3>` or `</MAP>`, with the byte offset of its `<`.
+struct Tag<'a> {
+    pos: usize,
+    name: &'a str,
+    closing: bool,
+    args: &'a str,
+}
+
+/// Reads the script.txt grammar directly.
+/// Only text in the form that `stringify_script_txt` writes is accepted, so that the two give
+/// back each other. Errors are reported with the line and column of the offending tag.
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    char_to_code: HashMap<char, u8>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            pos: 0,
+            char_to_code: reverse_code_map(),
+        }
+    }
+
+    fn error(&self, pos: usize, message: impl std::fmt::Display) -> anyhow::Error {
+        let before = &self.text[..pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map_or(0, |x| x + 1)..]
+            .chars()
+            .count()
+            + 1;
+        anyhow!("{}:{}: {}", line, column, message)
+    }
+
+    fn is_eof(&self) -> bool {
+        self.pos == self.text.len()
+    }
+
+    fn peek_tag(&self) -> Result<Tag<'a>> {
+        let pos = self.pos;
+        let text = self.text;
+        let rest = &text[pos..];
+        if !rest.starts_with('<') {
+            return Err(self.error(pos, "expected a tag"));
+        }
+        let Some(end) = rest.find('>') else {
+            return Err(self.error(pos, "unterminated tag"));
+        };
+        let inner = &rest[1..end];
+        let (closing, inner) = match inner.strip_prefix('/') {
+            Some(inner) => (true, inner),
+            None => (false, inner),
+        };
+        let (name, args) = match inner.split_once(' ') {
+            Some(_) if closing => return Err(self.error(pos, "closing tag with values")),
+            Some((name, args)) => (name, args),
+            None => (inner, ""),
+        };
+        Ok(Tag {
+            pos,
+            name,
+            closing,
+            args,
         })
-        .collect();
-    if cfg!(debug_assertions) {
-        let first_shop = shop_items_data::parse(&talks[252])?;
-        debug_assert_eq!(first_shop.0.number(), SubWeapon::HandScanner as u8);
-        debug_assert_eq!(first_shop.0.price(), 20);
-        debug_assert_eq!(first_shop.0.flag(), 696);
-        debug_assert_eq!(first_shop.1.number(), SubWeapon::Ammunition as u8);
-        debug_assert_eq!(first_shop.1.price(), 500);
-        debug_assert_eq!(first_shop.1.flag(), 65279);
-        debug_assert_eq!(first_shop.2.number(), SubWeapon::Buckler as u8);
-        debug_assert_eq!(first_shop.2.price(), 80);
-        debug_assert_eq!(first_shop.2.flag(), 697);
     }
-    let worlds: Vec<World> = root
-        .iter()
-        .filter(|world| world.value().name() == "world")
-        .map(|world| {
-            Ok(World {
-                number: u8::try_from(parse_attrs(&world.value().attrs)?[0])?,
-                fields: world
-                    .child_elements()
-                    .filter(|field| field.value().name() == "field")
-                    .map(|field| {
-                        let children = flat_children(field);
-                        let mut attrs = parse_attrs(&field.value().attrs)?.into_iter();
-                        Ok(Field {
-                            attrs: (
-                                u8::try_from(attrs.next().ok_or(anyhow!("No attributes found"))?)?,
-                                u8::try_from(attrs.next().ok_or(anyhow!("No attributes found"))?)?,
-                                u8::try_from(attrs.next().ok_or(anyhow!("No attributes found"))?)?,
-                                u8::try_from(attrs.next().ok_or(anyhow!("No attributes found"))?)?,
-                                u8::try_from(attrs.next().ok_or(anyhow!("No attributes found"))?)?,
-                            ),
-                            chip_line: {
-                                let chip_line = children
-                                    .iter()
-                                    .find(|child| child.value().name() == "chipline")
-                                    .ok_or_else(|| anyhow!("No CHIPLINE found"))?;
-                                let attrs = parse_attrs(&chip_line.value().attrs)?;
-                                (u16::try_from(attrs[0])?, u16::try_from(attrs[1])?)
-                            },
-                            hits: children
-                                .iter()
-                                .filter(|child| child.value().name() == "hit")
-                                .map(|&child| {
-                                    let attrs = parse_attrs(&child.value().attrs)?;
-                                    Ok((i16::try_from(attrs[0])?, i16::try_from(attrs[1])?))
-                                })
-                                .collect::<Result<_>>()?,
-                            animes: children
-                                .iter()
-                                .filter(|child| child.value().name() == "anime")
-                                .map(|&child| {
-                                    let attrs = parse_attrs(&child.value().attrs)?;
-                                    attrs
-                                        .into_iter()
-                                        .map(|x| Ok(u16::try_from(x)?))
-                                        .collect::<Result<Vec<_>>>()
-                                })
-                                .collect::<Result<_>>()?,
-                            objects: children
-                                .iter()
-                                .filter(|child| child.value().name() == "object")
-                                .map(|&child| {
-                                    let obj = parse_object(child)?;
-                                    let Object::Unknown(obj) = obj else {
-                                        bail!("Expected UnknownObject")
-                                    };
-                                    Ok(obj)
-                                })
-                                .collect::<Result<_>>()?,
-                            maps: children
-                                .iter()
-                                .filter(|child| child.value().name() == "map")
-                                .map(|&child| {
-                                    let attrs = parse_attrs(&child.value().attrs)?;
-                                    let map_children = flat_children(child);
-                                    Ok(Map {
-                                        attrs: (
-                                            u8::try_from(attrs[0])?,
-                                            u8::try_from(attrs[1])?,
-                                            u8::try_from(attrs[2])?,
-                                        ),
-                                        up: {
-                                            let up = map_children
-                                                .iter()
-                                                .find(|x| x.value().name() == "up")
-                                                .ok_or_else(|| anyhow!("No UP found"))?;
-                                            let attrs = parse_attrs(&up.value().attrs)?;
-                                            (
-                                                i8::try_from(attrs[0])?,
-                                                i8::try_from(attrs[1])?,
-                                                i8::try_from(attrs[2])?,
-                                                i8::try_from(attrs[3])?,
-                                            )
-                                        },
-                                        right: {
-                                            let up = map_children
-                                                .iter()
-                                                .find(|x| x.value().name() == "right")
-                                                .ok_or_else(|| anyhow!("No RIGHT found"))?;
-                                            let attrs = parse_attrs(&up.value().attrs)?;
-                                            (
-                                                i8::try_from(attrs[0])?,
-                                                i8::try_from(attrs[1])?,
-                                                i8::try_from(attrs[2])?,
-                                                i8::try_from(attrs[3])?,
-                                            )
-                                        },
-                                        down: {
-                                            let down = map_children
-                                                .iter()
-                                                .find(|x| x.value().name() == "down")
-                                                .ok_or_else(|| anyhow!("No DOWN found"))?;
-                                            let attrs = parse_attrs(&down.value().attrs)?;
-                                            (
-                                                i8::try_from(attrs[0])?,
-                                                i8::try_from(attrs[1])?,
-                                                i8::try_from(attrs[2])?,
-                                                i8::try_from(attrs[3])?,
-                                            )
-                                        },
-                                        left: {
-                                            let left = map_children
-                                                .iter()
-                                                .find(|x| x.value().name() == "left")
-                                                .ok_or_else(|| anyhow!("No LEFT found"))?;
-                                            let attrs = parse_attrs(&left.value().attrs)?;
-                                            (
-                                                i8::try_from(attrs[0])?,
-                                                i8::try_from(attrs[1])?,
-                                                i8::try_from(attrs[2])?,
-                                                i8::try_from(attrs[3])?,
-                                            )
-                                        },
-                                        objects: map_children
-                                            .iter()
-                                            .filter(|object| object.value().name() == "object")
-                                            .map(|&object| parse_object(object))
-                                            .collect::<Result<_>>()?,
-                                    })
-                                })
-                                .collect::<Result<_>>()?,
-                        })
-                    })
-                    .collect::<Result<_>>()?,
+
+    fn next_tag(&mut self) -> Result<Tag<'a>> {
+        let tag = self.peek_tag()?;
+        self.pos += self.text[self.pos..].find('>').unwrap() + 1;
+        // Every tag ends its line. Other whitespace, such as CRLF, is not accepted.
+        if !self.text[self.pos..].starts_with('\n') {
+            return Err(self.error(self.pos, "expected a line break"));
+        }
+        self.pos += 1;
+        Ok(tag)
+    }
+
+    fn expect_open(&mut self, name: &str) -> Result<Tag<'a>> {
+        let tag = self.next_tag()?;
+        if tag.closing || tag.name != name {
+            return Err(self.error(tag.pos, format!("expected <{}>", name)));
+        }
+        Ok(tag)
+    }
+
+    fn expect_close(&mut self, name: &str) -> Result<()> {
+        let tag = self.next_tag()?;
+        if !tag.closing || tag.name != name {
+            return Err(self.error(tag.pos, format!("expected </{}>", name)));
+        }
+        Ok(())
+    }
+
+    /// Whether the next tag opens `name`. A closing tag of `parent` ends the children.
+    fn next_child(&self, parent: &str) -> Result<Option<Tag<'a>>> {
+        let tag = self.peek_tag()?;
+        if tag.closing && tag.name == parent {
+            return Ok(None);
+        }
+        if tag.closing {
+            return Err(self.error(tag.pos, format!("expected </{}>", parent)));
+        }
+        Ok(Some(tag))
+    }
+
+    fn args<T: TryFrom<i32>, const N: usize>(&self, tag: &Tag) -> Result<[T; N]> {
+        let args = self.arg_list(tag)?;
+        if args.len() != N {
+            let message = format!("<{}> needs {} values, found {}", tag.name, N, args.len());
+            return Err(self.error(tag.pos, message));
+        }
+        let values: Vec<_> = args
+            .into_iter()
+            .map(|x| {
+                T::try_from(x).map_err(|_| self.error(tag.pos, format!("out of range: {}", x)))
             })
-        })
-        .collect::<Result<_>>()?;
+            .collect::<Result<_>>()?;
+        Ok(values.try_into().ok().unwrap())
+    }
 
-    debug_assert_eq!(talks.len(), 905);
-    debug_assert_eq!(worlds[0].fields[0].objects[0].starts[0].flag, 99999);
-    debug_assert_eq!(worlds[0].fields[0].maps[0].objects[5].starts()[0].flag, 58);
-    Ok((talks, worlds))
-}
+    fn arg_list<T: TryFrom<i32>>(&self, tag: &Tag) -> Result<Vec<T>> {
+        tag.args
+            .split(',')
+            .map(|x| {
+                let value: i32 = x
+                    .parse()
+                    .ok()
+                    // Such as `01` or `+1`, which would not be written back as they are.
+                    .filter(|value: &i32| value.to_string() == x)
+                    .ok_or_else(|| self.error(tag.pos, format!("invalid number: {:?}", x)))?;
+                T::try_from(value).map_err(|_| self.error(tag.pos, format!("out of range: {}", x)))
+            })
+            .collect()
+    }
 
-fn parse_attrs(attrs: &Attributes) -> Result<Vec<i32>> {
-    Ok(attrs
-        .keys()
-        .next()
-        .ok_or(anyhow!("No attributes found"))?
-        .local
-        .split(',')
-        .map(|x| x.parse::<i32>())
-        .collect::<Result<_, _>>()?)
-}
+    fn talk(&mut self) -> Result<Talk> {
+        let tag = self.expect_open("TALK")?;
+        if !tag.args.is_empty() {
+            return Err(self.error(tag.pos, "<TALK> with values"));
+        }
+        // The text starts after the line break of the tag and may contain `<` and `>`.
+        let start = self.pos;
+        let Some(len) = self.text[start..].find("</TALK>") else {
+            return Err(self.error(start, "unterminated <TALK>"));
+        };
+        let mut data = Vec::with_capacity(len);
+        for (i, c) in self.text[start..start + len].char_indices() {
+            let Some(&code) = self.char_to_code.get(&c) else {
+                return Err(self.error(start + i, format!("invalid character: {:?}", c)));
+            };
+            data.push(code);
+        }
+        self.pos = start + len;
+        self.expect_close("TALK")?;
+        Ok(Talk::new(data))
+    }
 
-fn flat_children(root: ElementRef) -> Vec<ElementRef> {
-    root.child_elements()
-        .map(|x| {
-            if x.child_elements().count() == 0 {
-                return vec![x];
-            }
-            if x.value().name() == "object" || x.value().name() == "map" {
-                return vec![x];
-            }
-            let mut vec = vec![x];
-            vec.append(&mut flat_children(x));
-            vec
+    fn unknown_object(&mut self) -> Result<(usize, UnknownObject)> {
+        let tag = self.expect_open("OBJECT")?;
+        let [number, x, y, op1, op2, op3, op4] = self.args::<i32, 7>(&tag)?;
+        let number = u16::try_from(number)
+            .map_err(|_| self.error(tag.pos, format!("out of range: {}", number)))?;
+        let mut starts = Vec::new();
+        while self.next_child("OBJECT")?.is_some() {
+            let start = self.expect_open("START")?;
+            let [flag, run_when] = self.args::<i32, 2>(&start)?;
+            let flag = u32::try_from(flag)
+                .map_err(|_| self.error(start.pos, format!("out of range: {}", flag)))?;
+            let run_when = match run_when {
+                0 => false,
+                1 => true,
+                _ => return Err(self.error(start.pos, format!("out of range: {}", run_when))),
+            };
+            starts.push(Start { flag, run_when });
+        }
+        self.expect_close("OBJECT")?;
+        let obj = UnknownObject {
+            number,
+            x,
+            y,
+            op1,
+            op2,
+            op3,
+            op4,
+            starts,
+        };
+        Ok((tag.pos, obj))
+    }
+
+    fn object(&mut self) -> Result<Object> {
+        let (pos, obj) = self.unknown_object()?;
+        let UnknownObject {
+            number,
+            x,
+            y,
+            op1,
+            op2,
+            op3,
+            op4,
+            starts,
+        } = obj;
+        Object::new(number, x, y, op1, op2, op3, op4, starts).map_err(|e| self.error(pos, e))
+    }
+
+    fn map(&mut self) -> Result<Map> {
+        let tag = self.expect_open("MAP")?;
+        let [a, b, c] = self.args(&tag)?;
+        let mut directions = Vec::with_capacity(4);
+        for name in ["UP", "RIGHT", "DOWN", "LEFT"] {
+            let tag = self.expect_open(name)?;
+            let [a, b, c, d] = self.args(&tag)?;
+            directions.push((a, b, c, d));
+        }
+        let mut objects = Vec::new();
+        while self.next_child("MAP")?.is_some() {
+            objects.push(self.object()?);
+        }
+        self.expect_close("MAP")?;
+        Ok(Map {
+            attrs: (a, b, c),
+            up: directions[0],
+            right: directions[1],
+            down: directions[2],
+            left: directions[3],
+            objects,
         })
-        .reduce(|mut p, mut c| {
-            p.append(&mut c);
-            p
+    }
+
+    fn field(&mut self) -> Result<Field> {
+        let tag = self.expect_open("FIELD")?;
+        let [a, b, c, d, e] = self.args(&tag)?;
+        let mut chip_line = None;
+        let mut hits = Vec::new();
+        let mut animes = Vec::new();
+        let mut objects = Vec::new();
+        let mut maps = Vec::new();
+        while let Some(child) = self.next_child("FIELD")? {
+            match child.name {
+                "CHIPLINE" => {
+                    let tag = self.next_tag()?;
+                    let [x, y] = self.args(&tag)?;
+                    chip_line = Some((x, y));
+                }
+                "HIT" => {
+                    let tag = self.next_tag()?;
+                    let [x, y] = self.args(&tag)?;
+                    hits.push((x, y));
+                }
+                "ANIME" => {
+                    let tag = self.next_tag()?;
+                    animes.push(self.arg_list(&tag)?);
+                }
+                // Objects directly under <FIELD> are kept as they are.
+                "OBJECT" => objects.push(self.unknown_object()?.1),
+                "MAP" => maps.push(self.map()?),
+                _ => return Err(self.error(child.pos, format!("unexpected <{}>", child.name))),
+            }
+        }
+        let Some(chip_line) = chip_line else {
+            return Err(self.error(tag.pos, "<CHIPLINE> not found"));
+        };
+        self.expect_close("FIELD")?;
+        Ok(Field {
+            attrs: (a, b, c, d, e),
+            chip_line,
+            hits,
+            animes,
+            objects,
+            maps,
         })
-        .unwrap_or_default()
+    }
+
+    fn world(&mut self) -> Result<World> {
+        let tag = self.expect_open("WORLD")?;
+        let [number] = self.args(&tag)?;
+        let mut fields = Vec::new();
+        while self.next_child("WORLD")?.is_some() {
+            fields.push(self.field()?);
+        }
+        self.expect_close("WORLD")?;
+        Ok(World { number, fields })
+    }
+
+    fn script(&mut self) -> Result<(Vec<Talk>, Vec<World>)> {
+        let mut talks = Vec::new();
+        let mut worlds = Vec::new();
+        while !self.is_eof() {
+            let tag = self.peek_tag()?;
+            match (tag.closing, tag.name) {
+                (false, "TALK") if worlds.is_empty() => talks.push(self.talk()?),
+                (false, "WORLD") => worlds.push(self.world()?),
+                _ => return Err(self.error(tag.pos, format!("unexpected <{}>", tag.name))),
+            }
+        }
+        Ok((talks, worlds))
+    }
 }
 
-fn parse_object(object: ElementRef) -> Result<Object> {
-    let attrs = parse_attrs(&object.value().attrs)?;
-    Object::new(
-        u16::try_from(attrs[0])?,
-        attrs[1],
-        attrs[2],
-        attrs[3],
-        attrs[4],
-        attrs[5],
-        attrs[6],
-        flat_children(object)
-            .iter()
-            .map(|x| {
-                let start_attrs = parse_attrs(&x.value().attrs)?;
-                Ok(Start {
-                    flag: u32::try_from(start_attrs[0])?,
-                    run_when: start_attrs[1] != 0,
-                })
-            })
-            .collect::<Result<_>>()?,
-    )
+pub fn parse_script_txt(text: &str) -> Result<(Vec<Talk>, Vec<World>)> {
+    Parser::new(text).script()
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -388,3 +467,133 @@ pub fn stringify_script_txt(talks: &[Talk], worlds: &[World]) -> String {
     ]
     .join("")
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    const SCRIPT_TXT: &str = "<TALK>
+A<B>
+C</TALK>
+<TALK>
+</TALK>
+<WORLD 0>
+<FIELD 1,2,3,4,5>
+<CHIPLINE 10,20>
+<HIT 1,2>
+<ANIME 1,2,3>
+<OBJECT 20,1,2,3,4,5,6>
+<START 99999,1>
+</OBJECT>
+<MAP 0,1,2>
+<UP 0,0,0,0>
+<RIGHT 0,1,0,0>
+<DOWN 0,0,-1,0>
+<LEFT 0,0,0,1>
+<OBJECT 20,-1,2,3,4,5,6>
+</OBJECT>
+</MAP>
+</FIELD>
+</WORLD>
+";
+
+    #[test]
+    fn test_parse_script_txt() -> Result<()> {
+        let (talks, worlds) = parse_script_txt(SCRIPT_TXT)?;
+        assert_eq!(talks.len(), 2);
+        assert_eq!(talks[0].to_string(), "A<B>\nC");
+        assert_eq!(worlds[0].fields[0].maps[0].objects.len(), 1);
+        assert_eq!(stringify_script_txt(&talks, &worlds), SCRIPT_TXT);
+
+        let broken = SCRIPT_TXT.replace("<HIT 1,2>", "<HIT 1>");
+        let e = parse_script_txt(&broken).err().unwrap();
+        assert!(e.to_string().starts_with("9:1: "), "{}", e);
+        let broken = SCRIPT_TXT.replace("</MAP>\n", "");
+        let e = parse_script_txt(&broken).err().unwrap();
+        assert!(e.to_string().starts_with("21:1: "), "{}", e);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_script_txt_rejects_other_forms() {
+        let cases = [
+            (SCRIPT_TXT.replace('\n', "\r\n"), "1:7: "),
+            (SCRIPT_TXT.replace("<HIT 1,2>\n", "<HIT 1,2>\n\n"), "10:1: "),
+            (SCRIPT_TXT.replace("<CHIPLINE", "  <CHIPLINE"), "8:1: "),
+            (SCRIPT_TXT.replace("<HIT 1,2>", "<HIT 01,2>"), "9:1: "),
+            (
+                SCRIPT_TXT.replace("<START 99999,1>", "<START 99999,2>"),
+                "12:1: ",
+            ),
+            (SCRIPT_TXT.replace("</MAP>", "</MAP 0>"), "21:1: "),
+            (format!("{}\n", SCRIPT_TXT), "24:1: "),
+        ];
+        for (txt, position) in cases {
+            let e = parse_script_txt(&txt).err().unwrap();
+            assert!(e.to_string().starts_with(position), "{}", e);
+        }
+    }
+
+    type Parsed = (Vec<Talk>, Vec<World>);
+
+    /// A script of about the size of the vanilla one.
+    fn large_script_txt() -> Result<String> {
+        let talks = vec![Talk::from_text("ABCDEFGHIJ\nKLMNOPQRST"); 905];
+        let object = |i: i32| {
+            let starts = vec![Start {
+                flag: 7000 + i as u32,
+                run_when: i % 2 == 0,
+            }];
+            Object::new(20, i * 1024, i * 2048, 800 + i, 1, 2, 3, starts)
+        };
+        let map = |i: u8| -> Result<Map> {
+            Ok(Map {
+                attrs: (i, 0, 0),
+                up: (0, 0, 0, 0),
+                right: (0, 1, 0, 0),
+                down: (0, 0, -1, 0),
+                left: (0, 0, 0, 1),
+                objects: (0..20).map(object).collect::<Result<_>>()?,
+            })
+        };
+        let field = |i: u8| -> Result<Field> {
+            Ok(Field {
+                attrs: (i, 2, 3, 4, 5),
+                chip_line: (10, 20),
+                hits: vec![(1, 2); 10],
+                animes: vec![vec![1, 2, 3]; 10],
+                objects: vec![],
+                maps: (0..30).map(map).collect::<Result<_>>()?,
+            })
+        };
+        let worlds = vec![World {
+            number: 0,
+            fields: (0..20).map(field).collect::<Result<_>>()?,
+        }];
+        Ok(stringify_script_txt(&talks, &worlds))
+    }
+
+    /// Compares with the scraper path that the parser replaced. Run with `--nocapture`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_parse_script_txt() -> Result<()> {
+        const RUNS: u32 = 10;
+        let txt = large_script_txt()?;
+        let bench = |parse: fn(&str) -> Result<Parsed>| -> Result<_> {
+            let start = Instant::now();
+            for _ in 0..RUNS {
+                let (talks, worlds) = parse(&txt)?;
+                assert_eq!(stringify_script_txt(&talks, &worlds), txt);
+            }
+            Ok(start.elapsed() / RUNS)
+        };
+        let html = bench(html::parse_script_txt)?;
+        let current = bench(parse_script_txt)?;
+        println!("{} bytes", txt.len());
+        println!("scraper: {:?}", html);
+        println!("current: {:?}", current);
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use anyhow::{anyhow, Result};
+use scraper::{node::Attributes, ElementRef, Html};
+
+use crate::script::data::{
+    object::{Object, Start, UnknownObject},
+    script::{Field, Map, World},
+    talk::Talk,
+};
+
+/// The parser that went through scraper's HTML parser, kept to benchmark the current one.
+pub fn parse_script_txt(text: &str) -> Result<(Vec<Talk>, Vec<World>)> {
+    let parser = Html::parse_fragment(text);
+    let root = parser.root_element().child_elements().collect::<Vec<_>>();
+    // NOTE: scraper converts all tag names to lowercase
+    let talks: Vec<_> = root
+        .iter()
+        .filter(|x| x.value().name() == "talk")
+        .map(|x| {
+            let talk = x
+                .text()
+                .collect::<String>()
+                .trim_start_matches('\n')
+                .to_owned();
+            Talk::from_text(&talk)
+        })
+        .collect();
+    let worlds: Vec<World> = root
+        .iter()
+        .filter(|world| world.value().name() == "world")
+        .map(|world| {
+            Ok(World {
+                number: u8::try_from(parse_attrs(&world.value().attrs)?[0])?,
+                fields: world
+                    .child_elements()
+                    .filter(|field| field.value().name() == "field")
+                    .map(|field| {
+                        let children = flat_children(field);
+                        let mut attrs = parse_attrs(&field.value().attrs)?.into_iter();
+                        Ok(Field {
+                            attrs: (
+                                u8::try_from(attrs.next().ok_or(anyhow!("No attributes found"))?)?,
+                                u8::try_from(attrs.next().ok_or(anyhow!("No attributes found"))?)?,
+                                u8::try_from(attrs.next().ok_or(anyhow!("No attributes found"))?)?,
+                                u8::try_from(attrs.next().ok_or(anyhow!("No attributes found"))?)?,
+                                u8::try_from(attrs.next().ok_or(anyhow!("No attributes found"))?)?,
+                            ),
+                            chip_line: {
+                                let chip_line = children
+                                    .iter()
+                                    .find(|child| child.value().name() == "chipline")
+                                    .ok_or_else(|| anyhow!("No CHIPLINE found"))?;
+                                let attrs = parse_attrs(&chip_line.value().attrs)?;
+                                (u16::try_from(attrs[0])?, u16::try_from(attrs[1])?)
+                            },
+                            hits: children
+                                .iter()
+                                .filter(|child| child.value().name() == "hit")
+                                .map(|&child| {
+                                    let attrs = parse_attrs(&child.value().attrs)?;
+                                    Ok((i16::try_from(attrs[0])?, i16::try_from(attrs[1])?))
+                                })
+                                .collect::<Result<_>>()?,
+                            animes: children
+                                .iter()
+                                .filter(|child| child.value().name() == "anime")
+                                .map(|&child| {
+                                    let attrs = parse_attrs(&child.value().attrs)?;
+                                    attrs
+                                        .into_iter()
+                                        .map(|x| Ok(u16::try_from(x)?))
+                                        .collect::<Result<Vec<_>>>()
+                                })
+                                .collect::<Result<_>>()?,
+                            objects: children
+                                .iter()
+                                .filter(|child| child.value().name() == "object")
+                                .map(|&child| {
+                                    let obj = parse_object(child)?;
+                                    Ok(UnknownObject {
+                                        number: obj.number(),
+                                        x: obj.x(),
+                                        y: obj.y(),
+                                        op1: obj.op1(),
+                                        op2: obj.op2(),
+                                        op3: obj.op3(),
+                                        op4: obj.op4(),
+                                        starts: obj.starts().to_vec(),
+                                    })
+                                })
+                                .collect::<Result<_>>()?,
+                            maps: children
+                                .iter()
+                                .filter(|child| child.value().name() == "map")
+                                .map(|&child| {
+                                    let attrs = parse_attrs(&child.value().attrs)?;
+                                    let map_children = flat_children(child);
+                                    Ok(Map {
+                                        attrs: (
+                                            u8::try_from(attrs[0])?,
+                                            u8::try_from(attrs[1])?,
+                                            u8::try_from(attrs[2])?,
+                                        ),
+                                        up: {
+                                            let up = map_children
+                                                .iter()
+                                                .find(|x| x.value().name() == "up")
+                                                .ok_or_else(|| anyhow!("No UP found"))?;
+                                            let attrs = parse_attrs(&up.value().attrs)?;
+                                            (
+                                                i8::try_from(attrs[0])?,
+                                                i8::try_from(attrs[1])?,
+                                                i8::try_from(attrs[2])?,
+                                                i8::try_from(attrs[3])?,
+                                            )
+                                        },
+                                        right: {
+                                            let up = map_children
+                                                .iter()
+                                                .find(|x| x.value().name() == "right")
+                                                .ok_or_else(|| anyhow!("No RIGHT found"))?;
+                                            let attrs = parse_attrs(&up.value().attrs)?;
+                                            (
+                                                i8::try_from(attrs[0])?,
+                                                i8::try_from(attrs[1])?,
+                                                i8::try_from(attrs[2])?,
+                                                i8::try_from(attrs[3])?,
+                                            )
+                                        },
+                                        down: {
+                                            let down = map_children
+                                                .iter()
+                                                .find(|x| x.value().name() == "down")
+                                                .ok_or_else(|| anyhow!("No DOWN found"))?;
+                                            let attrs = parse_attrs(&down.value().attrs)?;
+                                            (
+                                                i8::try_from(attrs[0])?,
+                                                i8::try_from(attrs[1])?,
+                                                i8::try_from(attrs[2])?,
+                                                i8::try_from(attrs[3])?,
+                                            )
+                                        },
+                                        left: {
+                                            let left = map_children
+                                                .iter()
+                                                .find(|x| x.value().name() == "left")
+                                                .ok_or_else(|| anyhow!("No LEFT found"))?;
+                                            let attrs = parse_attrs(&left.value().attrs)?;
+                                            (
+                                                i8::try_from(attrs[0])?,
+                                                i8::try_from(attrs[1])?,
+                                                i8::try_from(attrs[2])?,
+                                                i8::try_from(attrs[3])?,
+                                            )
+                                        },
+                                        objects: map_children
+                                            .iter()
+                                            .filter(|object| object.value().name() == "object")
+                                            .map(|&object| parse_object(object))
+                                            .collect::<Result<_>>()?,
+                                    })
+                                })
+                                .collect::<Result<_>>()?,
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok((talks, worlds))
+}
+
+fn parse_attrs(attrs: &Attributes) -> Result<Vec<i32>> {
+    Ok(attrs
+        .keys()
+        .next()
+        .ok_or(anyhow!("No attributes found"))?
+        .local
+        .split(',')
+        .map(|x| x.parse::<i32>())
+        .collect::<Result<_, _>>()?)
+}
+
+fn flat_children(root: ElementRef) -> Vec<ElementRef> {
+    root.child_elements()
+        .map(|x| {
+            if x.child_elements().count() == 0 {
+                return vec![x];
+            }
+            if x.value().name() == "object" || x.value().name() == "map" {
+                return vec![x];
+            }
+            let mut vec = vec![x];
+            vec.append(&mut flat_children(x));
+            vec
+        })
+        .reduce(|mut p, mut c| {
+            p.append(&mut c);
+            p
+        })
+        .unwrap_or_default()
+}
+
+fn parse_object(object: ElementRef) -> Result<Object> {
+    let attrs = parse_attrs(&object.value().attrs)?;
+    Object::new(
+        u16::try_from(attrs[0])?,
+        attrs[1],
+        attrs[2],
+        attrs[3],
+        attrs[4],
+        attrs[5],
+        attrs[6],
+        flat_children(object)
+            .iter()
+            .map(|x| {
+                let start_attrs = parse_attrs(&x.value().attrs)?;
+                Ok(Start {
+                    flag: u32::try_from(start_attrs[0])?,
+                    run_when: start_attrs[1] != 0,
+                })
+            })
+            .collect::<Result<_>>()?,
+    )
+}
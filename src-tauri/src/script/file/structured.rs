@@ -0,0 +1,347 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::script::data::{
+    object::{Object, Start, UnknownObject},
+    script::{Field, Map, Script, World},
+    talk::Talk,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    Yaml,
+    Json,
+}
+
+impl Format {
+    pub fn from_path(path: &str) -> Result<Self> {
+        let extension = path.rsplit_once('.').map(|x| x.1.to_ascii_lowercase());
+        Ok(match extension.as_deref() {
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("json") => Self::Json,
+            _ => bail!("unknown format: {} (expected .yaml, .yml or .json)", path),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StartDoc {
+    flag: u32,
+    run_when: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ObjectDoc {
+    number: u16,
+    x: i32,
+    y: i32,
+    op1: i32,
+    op2: i32,
+    op3: i32,
+    op4: i32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    starts: Vec<StartDoc>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MapDoc {
+    attrs: (u8, u8, u8),
+    up: (i8, i8, i8, i8),
+    right: (i8, i8, i8, i8),
+    down: (i8, i8, i8, i8),
+    left: (i8, i8, i8, i8),
+    objects: Vec<ObjectDoc>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FieldDoc {
+    attrs: (u8, u8, u8, u8, u8),
+    chip_line: (u16, u16),
+    hits: Vec<(i16, i16)>,
+    animes: Vec<Vec<u16>>,
+    objects: Vec<ObjectDoc>,
+    maps: Vec<MapDoc>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorldDoc {
+    number: u8,
+    fields: Vec<FieldDoc>,
+}
+
+/// `Script` with the talks in their readable form.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScriptDoc {
+    talks: Vec<String>,
+    worlds: Vec<WorldDoc>,
+}
+
+fn to_starts(starts: &[Start]) -> Vec<StartDoc> {
+    starts
+        .iter()
+        .map(|x| StartDoc {
+            flag: x.flag,
+            run_when: x.run_when,
+        })
+        .collect()
+}
+
+fn from_starts(starts: Vec<StartDoc>) -> Vec<Start> {
+    starts
+        .into_iter()
+        .map(|x| Start {
+            flag: x.flag,
+            run_when: x.run_when,
+        })
+        .collect()
+}
+
+fn to_object(obj: &Object) -> ObjectDoc {
+    ObjectDoc {
+        number: obj.number(),
+        x: obj.x(),
+        y: obj.y(),
+        op1: obj.op1(),
+        op2: obj.op2(),
+        op3: obj.op3(),
+        op4: obj.op4(),
+        starts: to_starts(obj.starts()),
+    }
+}
+
+fn to_unknown_object(obj: &UnknownObject) -> ObjectDoc {
+    ObjectDoc {
+        number: obj.number,
+        x: obj.x,
+        y: obj.y,
+        op1: obj.op1,
+        op2: obj.op2,
+        op3: obj.op3,
+        op4: obj.op4,
+        starts: to_starts(&obj.starts),
+    }
+}
+
+fn from_object(obj: ObjectDoc) -> Result<Object> {
+    let (number, x, y) = (obj.number, obj.x, obj.y);
+    Object::new(
+        number,
+        x,
+        y,
+        obj.op1,
+        obj.op2,
+        obj.op3,
+        obj.op4,
+        from_starts(obj.starts),
+    )
+    .map_err(|e| e.context(format!("object {} at ({}, {})", number, x, y)))
+}
+
+fn from_unknown_object(obj: ObjectDoc) -> UnknownObject {
+    UnknownObject {
+        number: obj.number,
+        x: obj.x,
+        y: obj.y,
+        op1: obj.op1,
+        op2: obj.op2,
+        op3: obj.op3,
+        op4: obj.op4,
+        starts: from_starts(obj.starts),
+    }
+}
+
+fn to_doc(script: &Script) -> ScriptDoc {
+    ScriptDoc {
+        talks: script.talks.iter().map(|x| x.to_readable()).collect(),
+        worlds: script
+            .worlds
+            .iter()
+            .map(|world| WorldDoc {
+                number: world.number,
+                fields: world
+                    .fields
+                    .iter()
+                    .map(|field| FieldDoc {
+                        attrs: field.attrs,
+                        chip_line: field.chip_line,
+                        hits: field.hits.clone(),
+                        animes: field.animes.clone(),
+                        objects: field.objects.iter().map(to_unknown_object).collect(),
+                        maps: field
+                            .maps
+                            .iter()
+                            .map(|map| MapDoc {
+                                attrs: map.attrs,
+                                up: map.up,
+                                right: map.right,
+                                down: map.down,
+                                left: map.left,
+                                objects: map.objects.iter().map(to_object).collect(),
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+fn from_doc(doc: ScriptDoc) -> Result<Script> {
+    let talks = doc
+        .talks
+        .iter()
+        .enumerate()
+        .map(|(i, x)| Talk::from_readable(x).map_err(|e| e.context(format!("talk {}", i))))
+        .collect::<Result<_>>()?;
+    let worlds = doc
+        .worlds
+        .into_iter()
+        .map(|world| {
+            Ok(World {
+                number: world.number,
+                fields: world
+                    .fields
+                    .into_iter()
+                    .map(|field| {
+                        Ok(Field {
+                            attrs: field.attrs,
+                            chip_line: field.chip_line,
+                            hits: field.hits,
+                            animes: field.animes,
+                            objects: field.objects.into_iter().map(from_unknown_object).collect(),
+                            maps: field
+                                .maps
+                                .into_iter()
+                                .map(|map| {
+                                    Ok(Map {
+                                        attrs: map.attrs,
+                                        up: map.up,
+                                        right: map.right,
+                                        down: map.down,
+                                        left: map.left,
+                                        objects: map
+                                            .objects
+                                            .into_iter()
+                                            .map(from_object)
+                                            .collect::<Result<_>>()?,
+                                    })
+                                })
+                                .collect::<Result<_>>()?,
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            })
+        })
+        .collect::<Result<_>>()?;
+    Ok(Script { talks, worlds })
+}
+
+pub fn export_script(script: &Script, format: Format) -> Result<String> {
+    let doc = to_doc(script);
+    Ok(match format {
+        Format::Yaml => serde_yaml::to_string(&doc)?,
+        Format::Json => serde_json::to_string_pretty(&doc)?,
+    })
+}
+
+pub fn import_script(text: &str, format: Format) -> Result<Script> {
+    let doc = match format {
+        Format::Yaml => serde_yaml::from_str(text)?,
+        Format::Json => serde_json::from_str(text)?,
+    };
+    from_doc(doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::read_script_dat_debug,
+        script::file::scriptconverter::{build_script_dat, is_valid_script_dat, read_script_dat},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_export_import_round_trip() -> Result<()> {
+        let talks = vec![
+            Talk::from_readable("A{flag:1234=1}{item:equipment:feather}\nB{cmd7}")?,
+            Talk::from_readable("{raw:00,0f}")?,
+            Talk::from_readable("")?,
+        ];
+        let starts = || {
+            vec![
+                Start {
+                    flag: 99999,
+                    run_when: true,
+                },
+                Start {
+                    flag: 58,
+                    run_when: false,
+                },
+            ]
+        };
+        let worlds = vec![World {
+            number: 0,
+            fields: vec![Field {
+                attrs: (1, 2, 3, 4, 5),
+                chip_line: (10, 20),
+                hits: vec![(1, -2)],
+                animes: vec![vec![1, 2, 3]],
+                objects: vec![UnknownObject {
+                    number: 20,
+                    x: 1,
+                    y: 2,
+                    op1: 3,
+                    op2: 4,
+                    op3: 5,
+                    op4: 6,
+                    starts: starts(),
+                }],
+                maps: vec![Map {
+                    attrs: (0, 1, 2),
+                    up: (0, 0, 0, 0),
+                    right: (0, 1, 0, 0),
+                    down: (0, 0, -1, 0),
+                    left: (0, 0, 0, 1),
+                    objects: vec![
+                        Object::new(1, 10, 20, 500, 3, 501, 0, starts())?,
+                        Object::new(20, -1, 2, 3, 4, 5, 6, vec![])?,
+                    ],
+                }],
+            }],
+        }];
+        let script = Script { talks, worlds };
+        let script_dat = build_script_dat(&script);
+        for format in [Format::Yaml, Format::Json] {
+            let text = export_script(&script, format)?;
+            let imported = import_script(&text, format)?;
+            assert_eq!(build_script_dat(&imported), script_dat, "{}", text);
+        }
+
+        let json = export_script(&script, Format::Json)?;
+        assert!(json.contains(r#""A{flag:1234=1}{item:equipment:feather}\nB{cmd7}""#));
+        let broken = json.replace("{cmd7}", "{cmd9}");
+        assert!(import_script(&broken, Format::Json).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "needs the vanilla script.dat in LMO_SCRIPT_DAT"]
+    fn test_export_import_vanilla_script() -> Result<()> {
+        let vanilla = read_script_dat_debug()?;
+        let script = read_script_dat(&vanilla)?;
+        for format in [Format::Yaml, Format::Json] {
+            let imported = import_script(&export_script(&script, format)?, format)?;
+            let script_dat = build_script_dat(&imported);
+            assert!(is_valid_script_dat(&script_dat), "{:?}", format);
+            assert_eq!(script_dat, vanilla, "{:?}", format);
+        }
+        Ok(())
+    }
+}
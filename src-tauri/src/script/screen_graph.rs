@@ -0,0 +1,352 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use num_traits::FromPrimitive;
+use serde::Serialize;
+
+use crate::{
+    dataset::{game_structure::GameStructure, spot::SpotName},
+    script::{
+        data::{
+            item::ChestItem,
+            object::{Object, Shop, ShopObject},
+            patch_rules::{is_not_a_spot, rules},
+            script::Script,
+            shop_items_data::ShopItem,
+        },
+        enums::{self, FieldNumber},
+    },
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Screen {
+    /// `field-room-screen`, as the exits refer to it.
+    pub id: String,
+    pub world: u8,
+    pub field: u8,
+    pub room: u8,
+    pub screen: u8,
+    /// Item spots of the game structure, as `type:name`.
+    pub spots: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Exit {
+    pub from: String,
+    pub direction: &'static str,
+    pub to: String,
+}
+
+/// Screens of every field and the exits between them.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenGraph {
+    pub screens: Vec<Screen>,
+    pub exits: Vec<Exit>,
+    /// Spots of the game structure that no screen holds.
+    pub unplaced_spots: Vec<String>,
+}
+
+fn screen_id(field: u8, room: u8, screen: u8) -> String {
+    format!("{}-{}-{}", field, room, screen)
+}
+
+fn shop_has_item(script: &Script, obj: &ShopObject, talk_item: enums::TalkItem) -> Result<bool> {
+    let talk_numbers = match Shop::try_from_shop_object(obj, &script.talks)? {
+        Shop::Storyteller(x) => vec![x.talk_number()],
+        Shop::ItemShop(_) => return Ok(false),
+        Shop::Eldest(x) => x.into_important_talk_numbers(),
+    };
+    for talk_number in talk_numbers {
+        if matches!(script.talks[talk_number as usize].item()?, Some((x, _)) if x == talk_item) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn sells(script: &Script, obj: &ShopObject, items: [Option<enums::ShopItem>; 3]) -> Result<bool> {
+    let Shop::ItemShop(shop) = Shop::try_from_shop_object(obj, &script.talks)? else {
+        return Ok(false);
+    };
+    let old = ShopItem::to_spot_shop_items(shop.items());
+    Ok(enums::ShopItem::matches_items(old, items))
+}
+
+/// The spots of the game structure at the object. A shop can hold several.
+fn spots_at(
+    game_structure: &GameStructure,
+    script: &Script,
+    field_number: FieldNumber,
+    obj: &Object,
+) -> Result<Vec<String>> {
+    let gs = game_structure;
+    let in_field = |x: FieldNumber| x == field_number;
+    let name = match obj {
+        Object::MainWeapon(obj) => gs
+            .main_weapon_shutters
+            .iter()
+            .find(|x| x.main_weapon() == obj.main_weapon().content)
+            .map(|x| format!("mainWeapon:{}", x.name().get())),
+        Object::SubWeapon(obj) => gs
+            .sub_weapon_shutters
+            .iter()
+            .find(|x| in_field(x.field_number()) && x.sub_weapon() == obj.sub_weapon().content)
+            .map(|x| format!("subWeapon:{}", x.name().get())),
+        Object::Chest(obj) => {
+            let content = match obj.item() {
+                ChestItem::None(_) => return Ok(vec![]),
+                ChestItem::Equipment(x) => enums::ChestItem::Equipment(x.content),
+                ChestItem::Rom(x) => enums::ChestItem::Rom(x.content),
+            };
+            gs.chests
+                .iter()
+                .find(|x| in_field(x.field_number()) && x.item() == content)
+                .map(|x| format!("chest:{}", x.name().get()))
+        }
+        Object::Seal(obj) => gs
+            .seals
+            .iter()
+            .find(|x| x.seal() == obj.seal().content)
+            .map(|x| format!("seal:{}", x.name().get())),
+        Object::Rom(obj) => gs
+            .roadside_roms
+            .iter()
+            .find(|x| x.rom() == obj.rom().content)
+            .map(|x| format!("rom:{}", x.name().get())),
+        Object::Shop(obj) => {
+            let mut names = Vec::new();
+            for spot in gs.shops.iter().filter(|x| in_field(x.field_number())) {
+                if sells(script, obj, spot.items())? {
+                    names.push(format!("shop:{}", spot.name().get()));
+                }
+            }
+            for spot in gs.talks.iter().filter(|x| in_field(x.field_number())) {
+                if shop_has_item(script, obj, spot.item())? {
+                    names.push(format!("talk:{}", spot.name().get()));
+                }
+            }
+            return Ok(names);
+        }
+        _ => None,
+    };
+    Ok(name.into_iter().collect())
+}
+
+fn all_spot_names(game_structure: &GameStructure) -> Vec<String> {
+    let gs = game_structure;
+    let spots: [(&str, Vec<&SpotName>); 7] = [
+        (
+            "mainWeapon",
+            gs.main_weapon_shutters.iter().map(|x| x.name()).collect(),
+        ),
+        (
+            "subWeapon",
+            gs.sub_weapon_shutters.iter().map(|x| x.name()).collect(),
+        ),
+        ("chest", gs.chests.iter().map(|x| x.name()).collect()),
+        ("seal", gs.seals.iter().map(|x| x.name()).collect()),
+        ("rom", gs.roadside_roms.iter().map(|x| x.name()).collect()),
+        ("shop", gs.shops.iter().map(|x| x.name()).collect()),
+        ("talk", gs.talks.iter().map(|x| x.name()).collect()),
+    ];
+    spots
+        .into_iter()
+        .flat_map(|(kind, names)| {
+            names
+                .into_iter()
+                .map(move |x| format!("{}:{}", kind, x.get()))
+        })
+        .collect()
+}
+
+/// Builds the screen graph from the maps of the script.
+/// Item spots are put on the screens that hold their objects.
+pub fn screen_graph(script: &Script, game_structure: &GameStructure) -> Result<ScreenGraph> {
+    let mut screens = Vec::new();
+    let mut exits = Vec::new();
+    let rules = rules()?;
+    for world in &script.worlds {
+        for field in &world.fields {
+            let field_number = FieldNumber::from_u8(field.attrs.0);
+            for map in &field.maps {
+                let (room, screen, _) = map.attrs;
+                let id = screen_id(field.attrs.0, room, screen);
+                let mut spots = Vec::new();
+                if let Some(field_number) = field_number {
+                    // The spots of the night surface belong to the surface.
+                    let spot_field_number = match field_number {
+                        FieldNumber::SurfaceNight => FieldNumber::Surface,
+                        field_number => field_number,
+                    };
+                    for obj in &map.objects {
+                        if is_not_a_spot(rules, field_number, map.attrs, obj) {
+                            continue;
+                        }
+                        let found = spots_at(game_structure, script, spot_field_number, obj)?;
+                        spots.extend(found);
+                    }
+                }
+                for (direction, (field, room, screen, _)) in [
+                    ("up", map.up),
+                    ("right", map.right),
+                    ("down", map.down),
+                    ("left", map.left),
+                ] {
+                    if field < 0 || room < 0 || screen < 0 {
+                        continue;
+                    }
+                    exits.push(Exit {
+                        from: id.clone(),
+                        direction,
+                        to: screen_id(field as u8, room as u8, screen as u8),
+                    });
+                }
+                screens.push(Screen {
+                    id,
+                    world: world.number,
+                    field: field.attrs.0,
+                    room,
+                    screen,
+                    spots,
+                });
+            }
+        }
+    }
+    let unplaced_spots = all_spot_names(game_structure)
+        .into_iter()
+        .filter(|name| !screens.iter().any(|x| x.spots.contains(name)))
+        .collect();
+    Ok(ScreenGraph {
+        screens,
+        exits,
+        unplaced_spots,
+    })
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Graphviz DOT with a cluster for each field.
+pub fn to_dot(graph: &ScreenGraph) -> String {
+    let mut dot = "digraph screens {\n  node [shape=box];\n".to_owned();
+    let mut fields: Vec<_> = graph.screens.iter().map(|x| (x.world, x.field)).collect();
+    fields.dedup();
+    for (world, field) in fields {
+        let name =
+            FieldNumber::from_u8(field).map_or_else(|| field.to_string(), |x| format!("{:?}", x));
+        writeln!(dot, "  subgraph \"cluster_{}_{}\" {{", world, field).unwrap();
+        writeln!(dot, "    label=\"{}\";", escape(&name)).unwrap();
+        for screen in graph
+            .screens
+            .iter()
+            .filter(|x| (x.world, x.field) == (world, field))
+        {
+            let label = [format!("{}-{}", screen.room, screen.screen)]
+                .into_iter()
+                .chain(screen.spots.iter().cloned())
+                .map(|x| escape(&x))
+                .collect::<Vec<_>>()
+                .join("\\n");
+            writeln!(dot, "    \"{}\" [label=\"{}\"];", screen.id, label).unwrap();
+        }
+        dot += "  }\n";
+    }
+    for exit in &graph.exits {
+        writeln!(
+            dot,
+            "  \"{}\" -> \"{}\" [label=\"{}\"];",
+            exit.from, exit.to, exit.direction
+        )
+        .unwrap();
+    }
+    dot += "}\n";
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dataset::spot::ChestSpot,
+        script::data::script::{Field, Map, World},
+    };
+
+    use super::*;
+
+    fn map(attrs: (u8, u8, u8), right: (i8, i8, i8, i8), objects: Vec<Object>) -> Map {
+        let none = (-1, -1, -1, 0);
+        Map {
+            attrs,
+            up: none,
+            right,
+            down: none,
+            left: none,
+            objects,
+        }
+    }
+
+    #[test]
+    fn test_screen_graph() -> Result<()> {
+        // Chest of the Shell Horn (equipment 1) opened by flag 700.
+        let chest = Object::new(1, 10, 20, 700, 1, 701, 0, vec![])?;
+        let script = Script {
+            talks: vec![],
+            worlds: vec![World {
+                number: 0,
+                fields: vec![Field {
+                    attrs: (1, 0, 0, 0, 0),
+                    chip_line: (0, 0),
+                    hits: vec![],
+                    animes: vec![],
+                    objects: vec![],
+                    maps: vec![
+                        map((0, 0, 0), (1, 0, 1, 0), vec![]),
+                        map((0, 1, 0), (-1, -1, -1, 0), vec![chest]),
+                    ],
+                }],
+            }],
+        };
+        let chest_spot = |name: &str, item| {
+            let name = SpotName::new(name.to_owned());
+            ChestSpot::new(FieldNumber::Surface, name, item, None)
+        };
+        let game_structure = GameStructure {
+            main_weapon_shutters: vec![],
+            sub_weapon_shutters: vec![],
+            chests: vec![
+                chest_spot(
+                    "shellHorn",
+                    enums::ChestItem::Equipment(enums::Equipment::ShellHorn),
+                ),
+                chest_spot(
+                    "feather",
+                    enums::ChestItem::Equipment(enums::Equipment::Feather),
+                ),
+            ],
+            seals: vec![],
+            roadside_roms: vec![],
+            shops: vec![],
+            talks: vec![],
+            events: vec![],
+        };
+        let graph = screen_graph(&script, &game_structure)?;
+        assert_eq!(
+            graph.screens.iter().map(|x| &x.id).collect::<Vec<_>>(),
+            ["1-0-0", "1-0-1"]
+        );
+        assert_eq!(graph.screens[1].spots, ["chest:shellHorn"]);
+        assert_eq!(graph.unplaced_spots, ["chest:feather"]);
+        assert_eq!(graph.exits.len(), 1);
+        assert_eq!(
+            (graph.exits[0].from.as_str(), graph.exits[0].to.as_str()),
+            ("1-0-0", "1-0-1")
+        );
+        let dot = to_dot(&graph);
+        assert!(dot.contains("\"1-0-1\" [label=\"0-1\\nchest:shellHorn\"];"));
+        assert!(dot.contains("\"1-0-0\" -> \"1-0-1\" [label=\"right\"];"));
+        Ok(())
+    }
+}
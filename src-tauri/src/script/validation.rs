@@ -0,0 +1,164 @@
+use num_traits::FromPrimitive;
+
+use crate::script::{
+    consts::VANILLA_TALK_COUNT,
+    data::{
+        object::{Shop, ShopObject},
+        patch_rules::unmatched_rules,
+        script::Script,
+        shop_items_data,
+    },
+    enums::{Equipment, MainWeapon, Rom, Seal, SubWeapon},
+};
+
+const FIRST_SHOP_TALK_NUMBER: usize = 252;
+
+fn variants<T: FromPrimitive>() -> impl Iterator<Item = T> {
+    (0..).map_while(T::from_u8)
+}
+
+fn validate_name_talks(script: &Script, issues: &mut Vec<String>) {
+    let talk_numbers = variants::<Rom>()
+        .map(|x| (format!("{:?}", x), x as usize))
+        .chain(variants::<Equipment>().map(|x| (format!("{:?}", x), 500 + x as usize)))
+        .chain(variants::<SubWeapon>().map(|x| (format!("{:?}", x), 645 + x as usize)));
+    for (name, talk_number) in talk_numbers {
+        if script
+            .talks
+            .get(talk_number)
+            .is_none_or(|x| x.as_bytes().is_empty())
+        {
+            issues.push(format!(
+                "item name talk {} ({}) is missing",
+                talk_number, name
+            ));
+        }
+    }
+}
+
+fn validate_first_shop(script: &Script, issues: &mut Vec<String>) {
+    let Some(talk) = script.talks.get(FIRST_SHOP_TALK_NUMBER) else {
+        return;
+    };
+    let items = match shop_items_data::parse(talk) {
+        Ok(items) => items,
+        Err(e) => {
+            issues.push(format!(
+                "talk {} is not a shop: {}",
+                FIRST_SHOP_TALK_NUMBER, e
+            ));
+            return;
+        }
+    };
+    let expected = [
+        SubWeapon::HandScanner,
+        SubWeapon::Ammunition,
+        SubWeapon::Buckler,
+    ];
+    let actual = [items.0.number(), items.1.number(), items.2.number()];
+    if actual != expected.map(|x| x as u8) {
+        issues.push(format!(
+            "talk {} should sell {:?}, found item numbers {:?}",
+            FIRST_SHOP_TALK_NUMBER, expected, actual
+        ));
+    }
+}
+
+fn validate_shop(script: &Script, obj: &ShopObject, issues: &mut Vec<String>) {
+    let shop = match Shop::try_from_shop_object(obj, &script.talks) {
+        Ok(shop) => shop,
+        Err(e) => {
+            issues.push(format!("shop at ({}, {}): {}", obj.x(), obj.y(), e));
+            return;
+        }
+    };
+    match shop {
+        Shop::ItemShop(shop) => {
+            // The editors rewrite the three item descriptions that follow the item data.
+            let talk_number = shop.item_data_talk_number() as usize;
+            if script.talks.len() <= talk_number + 3 {
+                issues.push(format!(
+                    "item descriptions of shop talk {} are missing",
+                    talk_number
+                ));
+            }
+        }
+        Shop::Storyteller(x) => {
+            let talk_number = x.talk_number();
+            if let Err(e) = x.into_talk().item() {
+                issues.push(format!("storyteller talk {}: {}", talk_number, e));
+            }
+        }
+        Shop::Eldest(_) => {}
+    }
+}
+
+/// Checks what the editors rely on, for script.dat files other than the known versions.
+/// Returns one message per issue found.
+pub fn validate(script: &Script) -> Vec<String> {
+    let mut issues = Vec::new();
+    if script.talks.len() < VANILLA_TALK_COUNT {
+        issues.push(format!(
+            "{} talks found, {} expected",
+            script.talks.len(),
+            VANILLA_TALK_COUNT
+        ));
+    }
+    validate_name_talks(script, &mut issues);
+    validate_first_shop(script, &mut issues);
+    for obj in script.shops() {
+        validate_shop(script, obj, &mut issues);
+    }
+    for main_weapon in variants::<MainWeapon>() {
+        if !script
+            .main_weapons()
+            .any(|x| x.main_weapon().content == main_weapon)
+        {
+            issues.push(format!("main weapon {:?} is missing", main_weapon));
+        }
+    }
+    for seal in variants::<Seal>() {
+        if !script.seals().any(|x| x.seal().content == seal) {
+            issues.push(format!("seal {:?} is missing", seal));
+        }
+    }
+    match unmatched_rules(script) {
+        Ok(names) => {
+            for name in names {
+                issues.push(format!("script patch rule {} matches no object", name));
+            }
+        }
+        Err(e) => issues.push(e.to_string()),
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script::data::talk::Talk;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_reports_missing_parts() {
+        let mut script = Script {
+            talks: vec![Talk::from_text("A"); VANILLA_TALK_COUNT],
+            worlds: vec![],
+        };
+        script.talks[500] = Talk::from_text("");
+        let issues = validate(&script);
+        assert!(!issues.iter().any(|x| x.contains("talks found")));
+        assert!(issues.contains(&"item name talk 500 (Msx) is missing".to_owned()));
+        assert!(issues
+            .iter()
+            .any(|x| x.starts_with("talk 252 is not a shop")));
+        assert!(issues.contains(&"main weapon Whip is missing".to_owned()));
+        assert!(issues.contains(&"seal Origin is missing".to_owned()));
+        assert!(issues.contains(&"script patch rule vimana matches no object".to_owned()));
+
+        script.talks.truncate(10);
+        assert!(
+            validate(&script).contains(&format!("10 talks found, {} expected", VANILLA_TALK_COUNT))
+        );
+    }
+}
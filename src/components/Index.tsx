@@ -2,28 +2,51 @@ import {
   Button,
   CircularProgress,
   CssBaseline,
+  FormControlLabel,
   IconButton,
+  MenuItem,
   Paper,
   Snackbar,
   SnackbarCloseReason,
+  Switch,
   TextField,
   Typography,
 } from '@mui/material';
 import React from 'react';
+import { Preset, SpoilerPolicy, SpoilerView } from '../types';
 import Difficulty from './Difficulty';
+import SpoilerViewer from './SpoilerViewer';
 
 export default function Index(props: {
   seed: string;
   installDirectory: string;
   difficulty: number;
+  presets: Preset[];
   snackbar: string;
   isProcessingApply: boolean;
   isProcessingRestore: boolean;
+  progress: string;
+  revealSpoiler: boolean;
+  spoiler: SpoilerView | null;
+  spoilerQuery: string;
+  spoilerPolicy: SpoilerPolicy['mode'];
+  spoilerPassword: string;
+  allowUnknownVersion: boolean;
+  confirmUnknownVanilla: boolean;
 
   onChangeSeed(seed: string): void;
   onChangeInstallDirectory(path: string): void;
+  onClickDetectInstallDirectory(): void;
   onChangeDifficulty(difficulty: number): void;
+  onChangePreset(name: string): void;
   onClickApply(): void;
+  onClickCancel(): void;
+  onChangeRevealSpoiler(reveal: boolean): void;
+  onChangeSpoilerQuery(query: string): void;
+  onChangeSpoilerPolicy(mode: SpoilerPolicy['mode']): void;
+  onChangeSpoilerPassword(password: string): void;
+  onChangeAllowUnknownVersion(allow: boolean): void;
+  onChangeConfirmUnknownVanilla(confirm: boolean): void;
   onClickRestore(): void;
   onCloseSnackbar(
     event: React.SyntheticEvent<any> | Event,
@@ -43,13 +66,35 @@ export default function Index(props: {
         }}
       >
         <Configs {...props} />
+        <SpoilerViewer
+          reveal={props.revealSpoiler}
+          spoiler={props.spoiler}
+          query={props.spoilerQuery}
+          onChangeReveal={props.onChangeRevealSpoiler}
+          onChangeQuery={props.onChangeSpoilerQuery}
+        />
         <div
           style={{
             marginTop: 16,
             display: 'flex',
             justifyContent: 'flex-end',
+            alignItems: 'center',
           }}
         >
+          {!props.isProcessingApply ? (
+            ''
+          ) : (
+            <>
+              <Typography style={{ fontSize: 14 }}>{props.progress}</Typography>
+              <Button
+                color="inherit"
+                style={{ marginLeft: 16, marginRight: 16 }}
+                onClick={props.onClickCancel}
+              >
+                Cancel
+              </Button>
+            </>
+          )}
           <div style={{ position: 'relative' }}>
             <Button
               variant="contained"
@@ -127,10 +172,21 @@ function Configs(props: {
   seed: string;
   installDirectory: string;
   difficulty: number;
+  presets: Preset[];
+  spoilerPolicy: SpoilerPolicy['mode'];
+  spoilerPassword: string;
+  allowUnknownVersion: boolean;
+  confirmUnknownVanilla: boolean;
 
   onChangeSeed(seed: string): void;
   onChangeInstallDirectory(path: string): void;
+  onClickDetectInstallDirectory(): void;
   onChangeDifficulty(difficulty: number): void;
+  onChangePreset(name: string): void;
+  onChangeSpoilerPolicy(mode: SpoilerPolicy['mode']): void;
+  onChangeSpoilerPassword(password: string): void;
+  onChangeAllowUnknownVersion(allow: boolean): void;
+  onChangeConfirmUnknownVanilla(confirm: boolean): void;
 }) {
   return (
     <Paper elevation={1} style={{ flex: 1, padding: 16 }}>
@@ -142,13 +198,90 @@ function Configs(props: {
         margin="dense"
         fullWidth
       />
+      <div style={{ display: 'flex', alignItems: 'center', gap: 8 }}>
+        <TextField
+          label="La-Mulana install directory"
+          value={props.installDirectory}
+          onChange={buildOnChangeInputElement(props.onChangeInstallDirectory)}
+          margin="dense"
+          fullWidth
+        />
+        <Button
+          variant="outlined"
+          color="inherit"
+          onClick={props.onClickDetectInstallDirectory}
+        >
+          Detect
+        </Button>
+      </div>
       <TextField
-        label="La-Mulana install directory"
-        value={props.installDirectory}
-        onChange={buildOnChangeInputElement(props.onChangeInstallDirectory)}
+        select
+        label="Preset"
+        value=""
+        onChange={buildOnChangeInputElement(props.onChangePreset)}
         margin="dense"
         fullWidth
+      >
+        {props.presets.map((preset) => (
+          <MenuItem key={preset.name} value={preset.name}>
+            {preset.name}
+          </MenuItem>
+        ))}
+      </TextField>
+      <div style={{ display: 'flex', gap: 8 }}>
+        <TextField
+          select
+          label="Spoiler log"
+          value={props.spoilerPolicy}
+          onChange={buildOnChangeInputElement((value) =>
+            props.onChangeSpoilerPolicy(value as SpoilerPolicy['mode'])
+          )}
+          margin="dense"
+          fullWidth
+        >
+          <MenuItem value="plaintext">Plaintext</MenuItem>
+          <MenuItem value="encrypted">Encrypted</MenuItem>
+          <MenuItem value="none">None</MenuItem>
+        </TextField>
+        {props.spoilerPolicy !== 'encrypted' ? (
+          ''
+        ) : (
+          <TextField
+            type="password"
+            label="Organizer key"
+            value={props.spoilerPassword}
+            onChange={buildOnChangeInputElement(props.onChangeSpoilerPassword)}
+            margin="dense"
+            fullWidth
+          />
+        )}
+      </div>
+      <FormControlLabel
+        control={
+          <Switch
+            checked={props.allowUnknownVersion}
+            onChange={(ev) =>
+              props.onChangeAllowUnknownVersion(ev.target.checked)
+            }
+          />
+        }
+        label="Allow unknown script.dat versions"
       />
+      {!props.allowUnknownVersion ? (
+        ''
+      ) : (
+        <FormControlLabel
+          control={
+            <Switch
+              checked={props.confirmUnknownVanilla}
+              onChange={(ev) =>
+                props.onChangeConfirmUnknownVanilla(ev.target.checked)
+              }
+            />
+          }
+          label="Keep this unmodified script.dat as the original"
+        />
+      )}
       <Typography sx={{ mt: 2, mb: 1, fontSize: 14 }}>Difficulty</Typography>
       <Difficulty
         difficulty={props.difficulty}
@@ -0,0 +1,88 @@
+import {
+  FormControlLabel,
+  Paper,
+  Switch,
+  TextField,
+  Typography,
+} from '@mui/material';
+import React from 'react';
+import { ShopView, SpoilerView, SpotView } from '../types';
+
+function sphereLabel(sphere: number | null) {
+  return sphere == null ? 'maps' : `sphere ${sphere}`;
+}
+
+function spotLine(spot: SpotView) {
+  return `${spot.name} = ${spot.item} (${sphereLabel(spot.sphere)})`;
+}
+
+function shopLine(shop: ShopView) {
+  const slots = shop.slots.map((slot) =>
+    slot == null ? '_' : `${slot.item} (${sphereLabel(slot.sphere)})`
+  );
+  return `${shop.name} = ${slots.join(', ')}`;
+}
+
+function matches(line: string, field: string, query: string) {
+  const q = query.toLowerCase();
+  return line.toLowerCase().includes(q) || field.toLowerCase().includes(q);
+}
+
+export default function SpoilerViewer(props: {
+  reveal: boolean;
+  spoiler: SpoilerView | null;
+  query: string;
+
+  onChangeReveal(reveal: boolean): void;
+  onChangeQuery(query: string): void;
+}) {
+  const fields = (props.spoiler?.fields ?? [])
+    .map((field) => ({
+      field: field.field,
+      lines: [
+        ...field.spots.map(spotLine),
+        ...field.shops.map(shopLine),
+      ].filter((line) => matches(line, field.field, props.query)),
+    }))
+    .filter((field) => field.lines.length > 0);
+  return (
+    <Paper elevation={1} style={{ marginTop: 16, padding: 16 }}>
+      <FormControlLabel
+        control={
+          <Switch
+            checked={props.reveal}
+            onChange={(ev) => props.onChangeReveal(ev.target.checked)}
+          />
+        }
+        label="Reveal spoiler"
+      />
+      {!props.reveal || props.spoiler == null ? (
+        ''
+      ) : (
+        <>
+          <TextField
+            label="Search"
+            value={props.query}
+            onChange={(ev) => props.onChangeQuery(ev.target.value)}
+            margin="dense"
+            fullWidth
+          />
+          <div style={{ maxHeight: 240, overflowY: 'auto' }}>
+            {fields.map((field) => (
+              <div key={field.field}>
+                <Typography sx={{ mt: 1, fontSize: 14, fontWeight: 'bold' }}>
+                  {field.field}
+                </Typography>
+                {field.lines.map((line) => (
+                  <Typography key={line} style={{ fontSize: 12 }}>
+                    {line}
+                  </Typography>
+                ))}
+              </div>
+            ))}
+          </div>
+        </>
+      )}
+    </Paper>
+  );
+}
@@ -1,8 +1,89 @@
 import { SnackbarCloseReason } from '@mui/material';
 import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import { error } from '@tauri-apps/plugin-log';
 import React from 'react';
 import { default as Component } from '../components/Index';
+import {
+  AppError,
+  InstallCandidate,
+  Preset,
+  Progress,
+  RestoreOutcome,
+  SpoilerPolicy,
+  SpoilerView,
+} from '../types';
+
+function isAppError(err: unknown): err is AppError {
+  return typeof err === 'object' && err != null && 'code' in err;
+}
+
+function toErrorMessage(err: unknown): string {
+  if (!isAppError(err)) {
+    return `${err}`;
+  }
+  switch (err.code) {
+    case 'installDirectoryNotFound':
+      return 'Unable to find La-Mulana install directory.';
+    case 'invalidScript':
+      return `Valid script is not found. Please re-install La-Mulana.\n${err.details}`;
+    case 'unknownScriptVersion':
+      return 'script.dat is not a known version. Please re-install La-Mulana, or allow unknown versions if it is a translation or a community patch.';
+    case 'unknownVanillaNotConfirmed':
+      return 'script.dat is not a known version, so it is kept as the original only on confirmation. Please make sure that it is neither randomized nor modified, then confirm it as the original.';
+    case 'backupNotFound':
+      return 'Backup is not found. Please randomize first.';
+    case 'backupBroken':
+      return 'Backup is broken. Please re-install La-Mulana.';
+    case 'backupFailed':
+      return `Failed to backup script.dat: ${err.details}`;
+    case 'backupReadFailed':
+      return `Failed to read the backups: ${err.details}`;
+    case 'unknownBackup':
+      return `Backup ${err.id} is not found.`;
+    case 'notRandomized':
+      return 'script.dat is not randomized.';
+    case 'gameStructureReadFailed':
+      return `Failed to read game structure files: ${err.details}`;
+    case 'cancelled':
+      return 'Cancelled.';
+    case 'spoilerNotFound':
+      return 'No spoiler yet. Please randomize first.';
+    case 'spoilerEncryptionFailed':
+      return `Failed to encrypt the spoiler log: ${err.details}`;
+    case 'spoilerDecryptionFailed':
+      return `Failed to decrypt the spoiler log: ${err.details}`;
+    case 'generationFailed':
+      return `Randomization failed: ${err.details}`;
+    case 'verificationFailed':
+      return `Verification failed: ${err.details}`;
+    case 'patchReadFailed':
+      return `Unable to read the patch file: ${err.details}`;
+    case 'patchFailed':
+      return `Patch failed: ${err.details}`;
+    case 'writeFailed':
+      return `Failed to write ${err.path}: ${err.details}`;
+    case 'storeFailed':
+      return `Failed to save settings: ${err.details}`;
+    case 'presetFailed':
+      return `Preset operation failed: ${err.details}`;
+  }
+}
+
+function toProgressMessage(progress: Progress): string {
+  switch (progress.stage) {
+    case 'readScript':
+      return 'Reading script...';
+    case 'buildStructure':
+      return 'Building game structure...';
+    case 'shuffle':
+      return `Shuffling... (${progress.tries} tries)`;
+    case 'applyStorage':
+      return 'Placing items...';
+    case 'write':
+      return 'Writing files...';
+  }
+}
 
 function toDifficulty(state: typeof initialState): number {
   if (state.absolutelyShuffle) {
@@ -30,6 +111,17 @@ const initialState = {
   shuffleSecretRoms: false,
   needGlitches: false,
   absolutelyShuffle: false,
+  presets: [] as Preset[],
+  progress: '',
+  revealSpoiler: false,
+  spoiler: null as SpoilerView | null,
+  spoilerQuery: '',
+  spoilerPolicy: 'plaintext' as SpoilerPolicy['mode'],
+  // Not saved, so that it doesn't stay on the racer's machine.
+  spoilerPassword: '',
+  allowUnknownVersion: false,
+  // Not saved, so that it is confirmed for the script.dat at hand.
+  confirmUnknownVanilla: false,
   snackbar: '',
   isProcessingApply: false,
   isProcessingRestore: false,
@@ -40,8 +132,20 @@ export default class Index extends React.Component<Props, typeof initialState> {
     super(props);
     this.onChangeSeed = this.onChangeSeed.bind(this);
     this.onChangeInstallDirectory = this.onChangeInstallDirectory.bind(this);
+    this.onClickDetectInstallDirectory =
+      this.onClickDetectInstallDirectory.bind(this);
     this.onChangeDifficulty = this.onChangeDifficulty.bind(this);
+    this.onChangePreset = this.onChangePreset.bind(this);
     this.onClickApply = this.onClickApply.bind(this);
+    this.onClickCancel = this.onClickCancel.bind(this);
+    this.onChangeRevealSpoiler = this.onChangeRevealSpoiler.bind(this);
+    this.onChangeSpoilerQuery = this.onChangeSpoilerQuery.bind(this);
+    this.onChangeSpoilerPolicy = this.onChangeSpoilerPolicy.bind(this);
+    this.onChangeSpoilerPassword = this.onChangeSpoilerPassword.bind(this);
+    this.onChangeAllowUnknownVersion =
+      this.onChangeAllowUnknownVersion.bind(this);
+    this.onChangeConfirmUnknownVanilla =
+      this.onChangeConfirmUnknownVanilla.bind(this);
     this.onClickRestore = this.onClickRestore.bind(this);
     this.onCloseSnackbar = this.onCloseSnackbar.bind(this);
     this.state = {
@@ -54,6 +158,24 @@ export default class Index extends React.Component<Props, typeof initialState> {
     };
   }
 
+  private unlistenProgress?: Promise<UnlistenFn>;
+
+  async componentDidMount() {
+    this.unlistenProgress = listen<Progress>('progress', (event) => {
+      this.setState({ progress: toProgressMessage(event.payload) });
+    });
+    try {
+      const presets: Preset[] = await invoke('list_presets');
+      this.setState({ ...this.state, presets });
+    } catch (err) {
+      console.error(err);
+    }
+  }
+
+  componentWillUnmount() {
+    this.unlistenProgress?.then((unlisten) => unlisten());
+  }
+
   private onChangeSeed(seed: string) {
     invoke('set_seed', { value: seed }).catch(error);
     this.setState({
@@ -70,6 +192,27 @@ export default class Index extends React.Component<Props, typeof initialState> {
     });
   }
 
+  private async onClickDetectInstallDirectory() {
+    let candidates: InstallCandidate[];
+    try {
+      candidates = await invoke('detect_install_directories', {
+        searchRoots: [],
+      });
+    } catch (err) {
+      console.error(err);
+      this.setState({ ...this.state, snackbar: toErrorMessage(err) });
+      return;
+    }
+    if (candidates.length === 0) {
+      this.setState({
+        ...this.state,
+        snackbar: 'Unable to find La-Mulana install directory.',
+      });
+      return;
+    }
+    this.onChangeInstallDirectory(candidates[0].path);
+  }
+
   private onChangeDifficulty(difficulty: number) {
     const shuffleSecretRoms = difficulty >= 1;
     const needGlitches = difficulty >= 2;
@@ -88,15 +231,45 @@ export default class Index extends React.Component<Props, typeof initialState> {
     });
   }
 
+  private onChangePreset(name: string) {
+    const preset = this.state.presets.find((x) => x.name === name);
+    if (preset == null) {
+      return;
+    }
+    const { seed, shuffleSecretRoms, needGlitches, absolutelyShuffle } =
+      preset.options;
+    // An empty seed keeps the one entered.
+    if (seed !== '') {
+      this.onChangeSeed(seed);
+    }
+    invoke('set_shuffle_secret_roms', { value: shuffleSecretRoms }).catch(
+      error
+    );
+    invoke('set_need_glitches', { value: needGlitches }).catch(error);
+    invoke('set_absolutely_shuffle', { value: absolutelyShuffle }).catch(error);
+    this.setState({
+      ...this.state,
+      ...(seed !== '' ? { seed } : {}),
+      shuffleSecretRoms,
+      needGlitches,
+      absolutelyShuffle,
+    });
+  }
+
   private async onClickApply() {
     this.setState({
       ...this.state,
       isProcessingApply: true,
+      progress: '',
       snackbar: '',
     });
+    const spoilerPolicy: SpoilerPolicy =
+      this.state.spoilerPolicy === 'encrypted'
+        ? { mode: 'encrypted', password: this.state.spoilerPassword }
+        : { mode: this.state.spoilerPolicy };
     let result: string;
     try {
-      result = await invoke('apply', {
+      await invoke('apply', {
         installDirectory: this.state.installDirectory,
         options: {
           seed: this.state.seed,
@@ -104,19 +277,74 @@ export default class Index extends React.Component<Props, typeof initialState> {
           needGlitches: this.state.needGlitches,
           absolutelyShuffle: this.state.absolutelyShuffle,
         },
+        applyOptions: {
+          spoilerPolicy,
+          allowUnknownVersion: this.state.allowUnknownVersion,
+          confirmUnknownVanilla:
+            this.state.allowUnknownVersion && this.state.confirmUnknownVanilla,
+        },
       });
+      result = 'Succeeded.';
+      if (this.state.revealSpoiler && spoilerPolicy.mode === 'plaintext') {
+        await this.loadSpoiler();
+      }
     } catch (err) {
       console.error(err);
-      result = `${err}`;
+      result = toErrorMessage(err);
     }
     this.setState({
       ...this.state,
       isProcessingApply: false,
       isProcessingRestore: false,
+      progress: '',
       snackbar: result,
     });
   }
 
+  private onClickCancel() {
+    invoke('cancel').catch(error);
+  }
+
+  private async onChangeRevealSpoiler(reveal: boolean) {
+    this.setState({ revealSpoiler: reveal, spoiler: null });
+    if (!reveal) {
+      return;
+    }
+    await this.loadSpoiler();
+  }
+
+  private async loadSpoiler() {
+    try {
+      const spoiler: SpoilerView | null = await invoke('spoiler', {
+        reveal: true,
+      });
+      this.setState({ spoiler });
+    } catch (err) {
+      console.error(err);
+      this.setState({ snackbar: toErrorMessage(err) });
+    }
+  }
+
+  private onChangeSpoilerQuery(query: string) {
+    this.setState({ spoilerQuery: query });
+  }
+
+  private onChangeSpoilerPolicy(mode: SpoilerPolicy['mode']) {
+    this.setState({ spoilerPolicy: mode });
+  }
+
+  private onChangeSpoilerPassword(password: string) {
+    this.setState({ spoilerPassword: password });
+  }
+
+  private onChangeAllowUnknownVersion(allow: boolean) {
+    this.setState({ allowUnknownVersion: allow });
+  }
+
+  private onChangeConfirmUnknownVanilla(confirm: boolean) {
+    this.setState({ confirmUnknownVanilla: confirm });
+  }
+
   private async onClickRestore() {
     this.setState({
       ...this.state,
@@ -125,12 +353,13 @@ export default class Index extends React.Component<Props, typeof initialState> {
     });
     let result: string;
     try {
-      result = await invoke('restore', {
+      const outcome: RestoreOutcome = await invoke('restore', {
         installDirectory: this.state.installDirectory,
       });
+      result = outcome === 'alreadyClean' ? 'Already clean.' : 'Succeeded.';
     } catch (err) {
       console.error(err);
-      result = `${err}`;
+      result = toErrorMessage(err);
     }
     this.setState({
       ...this.state,
@@ -160,8 +389,17 @@ export default class Index extends React.Component<Props, typeof initialState> {
         difficulty={toDifficulty(this.state)}
         onChangeSeed={this.onChangeSeed}
         onChangeInstallDirectory={this.onChangeInstallDirectory}
+        onClickDetectInstallDirectory={this.onClickDetectInstallDirectory}
         onChangeDifficulty={this.onChangeDifficulty}
+        onChangePreset={this.onChangePreset}
         onClickApply={this.onClickApply}
+        onClickCancel={this.onClickCancel}
+        onChangeRevealSpoiler={this.onChangeRevealSpoiler}
+        onChangeSpoilerQuery={this.onChangeSpoilerQuery}
+        onChangeSpoilerPolicy={this.onChangeSpoilerPolicy}
+        onChangeSpoilerPassword={this.onChangeSpoilerPassword}
+        onChangeAllowUnknownVersion={this.onChangeAllowUnknownVersion}
+        onChangeConfirmUnknownVanilla={this.onChangeConfirmUnknownVanilla}
         onClickRestore={this.onClickRestore}
         onCloseSnackbar={this.onCloseSnackbar}
       />
@@ -6,3 +6,93 @@ export interface InitialData {
   needGlitches: boolean;
   absolutelyShuffle: boolean;
 }
+
+export type AppError =
+  | { code: 'installDirectoryNotFound' }
+  | { code: 'invalidScript'; details: string }
+  | { code: 'unknownScriptVersion' }
+  | { code: 'unknownVanillaNotConfirmed' }
+  | { code: 'backupNotFound' }
+  | { code: 'backupBroken' }
+  | { code: 'backupFailed'; details: string }
+  | { code: 'backupReadFailed'; details: string }
+  | { code: 'unknownBackup'; id: string }
+  | { code: 'notRandomized' }
+  | { code: 'gameStructureReadFailed'; details: string }
+  | { code: 'generationFailed'; details: string }
+  | { code: 'cancelled' }
+  | { code: 'spoilerNotFound' }
+  | { code: 'spoilerEncryptionFailed'; details: string }
+  | { code: 'spoilerDecryptionFailed'; details: string }
+  | { code: 'verificationFailed'; details: string }
+  | { code: 'patchReadFailed'; details: string }
+  | { code: 'patchFailed'; details: string }
+  | { code: 'writeFailed'; path: string; details: string }
+  | { code: 'storeFailed'; details: string }
+  | { code: 'presetFailed'; details: string };
+
+export interface Backup {
+  id: string;
+  seed: string;
+  settings: string;
+  timestamp: number;
+}
+
+export interface RandomizeOptions {
+  seed: string;
+  shuffleSecretRoms: boolean;
+  needGlitches: boolean;
+  absolutelyShuffle: boolean;
+}
+
+export type SpoilerPolicy =
+  | { mode: 'none' }
+  | { mode: 'plaintext' }
+  | { mode: 'encrypted'; password: string };
+
+export interface Preset {
+  name: string;
+  builtIn: boolean;
+  options: RandomizeOptions;
+}
+
+export interface InstallCandidate {
+  path: string;
+  source: 'user' | 'steam' | 'proton' | 'wine' | 'lutris' | 'bottles' | 'gog';
+  evidence: 'knownScript' | 'backup';
+}
+
+export type Progress =
+  | { stage: 'readScript' }
+  | { stage: 'buildStructure' }
+  | { stage: 'shuffle'; tries: number }
+  | { stage: 'applyStorage' }
+  | { stage: 'write' };
+
+export interface SpotView {
+  kind: 'mainWeapon' | 'subWeapon' | 'chest' | 'seal' | 'rom' | 'talk';
+  name: string;
+  field: string;
+  item: string;
+  sphere: number | null;
+}
+
+export interface ShopView {
+  name: string;
+  field: string;
+  slots: ({ item: string; sphere: number | null } | null)[];
+}
+
+export interface CheckpointsView {
+  spots: SpotView[];
+  shops: ShopView[];
+  events: string[];
+}
+
+export interface SpoilerView {
+  spheres: CheckpointsView[];
+  maps: CheckpointsView;
+  fields: { field: string; spots: SpotView[]; shops: ShopView[] }[];
+}
+
+export type RestoreOutcome = 'restored' | 'alreadyClean';